@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use lambdaworks_math::{
+    field::{
+        element::FieldElement,
+        fields::fft_friendly::{
+            babybear::Babybear31PrimeField, stark_252_prime_field::Stark252PrimeField,
+        },
+        traits::IsFFTField,
+    },
+    polynomial::Polynomial,
+};
+use ntt::NTT;
+
+/// Compares `NTT::forward` against the FFT of LambdaWorks over the given field, for sizes `2^8` to `2^16`.
+fn bench_forward<F: IsFFTField>(c: &mut Criterion, field: &str) {
+    let mut group = c.benchmark_group(format!("forward/{}", field));
+    group.sample_size(10);
+
+    for log_n in (8..=16).step_by(2) {
+        let n = 1u64 << log_n;
+        let w = F::get_primitive_root_of_unity(log_n).unwrap();
+        let ntt = NTT::new(w, n);
+        let coeffs = (0..n)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+        let poly = Polynomial::new(&coeffs);
+
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::new("ntt", n), &coeffs, |b, coeffs| {
+            b.iter(|| ntt.forward(coeffs))
+        });
+        group.bench_with_input(BenchmarkId::new("lambdaworks", n), &poly, |b, poly| {
+            b.iter(|| Polynomial::evaluate_fft::<F>(poly, 1, None).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Compares the recursive NTT against the in-place iterative ones (DIT and DIF) over BabyBear, for sizes `2^12` to
+/// `2^22`. The in-place ones are given a fresh copy of the coefficients at each iteration.
+fn bench_in_place(c: &mut Criterion) {
+    type F = Babybear31PrimeField;
+    let mut group = c.benchmark_group("in_place/babybear");
+    group.sample_size(10);
+
+    for log_n in (12..=22).step_by(2) {
+        let n = 1u64 << log_n;
+        let w = F::get_primitive_root_of_unity(log_n).unwrap();
+        let ntt = NTT::new(w, n);
+        let coeffs = (0..n)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::new("recursive", n), &coeffs, |b, coeffs| {
+            b.iter(|| ntt.forward_recursive(coeffs))
+        });
+        group.bench_with_input(BenchmarkId::new("dit", n), &coeffs, |b, coeffs| {
+            b.iter_batched_ref(
+                || coeffs.clone(),
+                |values| ntt.forward_in_place(values),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("dif", n), &coeffs, |b, coeffs| {
+            b.iter_batched_ref(
+                || coeffs.clone(),
+                |values| ntt.forward_in_place_dif(values),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    bench_forward::<Babybear31PrimeField>(c, "babybear");
+    bench_forward::<Stark252PrimeField>(c, "stark252");
+    bench_in_place(c);
+}
+
+criterion_group!(benches, bench_ntt);
+criterion_main!(benches);
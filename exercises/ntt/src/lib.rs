@@ -1,2 +1,8 @@
 mod ntt;
-pub use ntt::NTT;
+pub use ntt::{bit_reverse_permute, NTT};
+
+mod poly;
+pub use poly::{poly_div_rem, poly_inverse_mod, poly_mul};
+
+mod mixed;
+pub use mixed::{BluesteinNTT, MixedRadixNTT, NTTPlan, MAX_PRIME_RADIX};
@@ -1,40 +1,617 @@
-use std::env;
-
-use lambdaworks_math::{
-    field::{element::FieldElement, test_fields::u64_test_field::U64Field},
-    polynomial::Polynomial,
+use std::{
+    cell::Cell,
+    env, fs,
+    io::{self, Read},
+    process,
 };
-use ntt::NTT;
 
-// define a prime field of order 17
-type F = U64Field<17>;
-type FE = FieldElement<F>;
+use lambdaworks_math::field::{element::FieldElement, errors::FieldError, traits::IsField};
+use ntt::{MixedRadixNTT, MAX_PRIME_RADIX, NTT};
+
+const USAGE: &str = "\
+Usage: ntt <forward|inverse|mul> --modulus <q> [options] [values...]
+
+Transforms a vector over the prime field of order q with a primitive root of unity of the given size, which is
+found automatically; mul multiplies two polynomials, i.e. the cyclic convolution of their coefficients.
+
+Options:
+  -q, --modulus <q>   prime modulus of the field
+  -n, --size <n>      size of the transform, which must divide q - 1 with prime factors of at most 13; the
+                      values are padded with zeros (default: number of values, or the size of the product)
+  -i, --input <file>  read the values from a file instead of the arguments
+  -x, --hex           print the results in hexadecimal
+  -j, --json          print the results as a JSON array instead of comma-separated values
+  -h, --help          print this message
+
+Values are decimal or 0x-prefixed hexadecimal, possibly negative, given either as CSV (separated by commas or
+whitespace) or as a JSON array. They are read from the arguments, the input file, or the standard input when
+neither is given. mul takes two vectors: two arguments, two lines of CSV, or a JSON array of two arrays.
+
+Example: ntt forward --modulus 17 0 1 2 3";
+
+thread_local! {
+    /// Modulus of `RuntimeField`, set from the arguments; thread-local so that the tests can use different ones.
+    static MODULUS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Prime field with a modulus that is only known at runtime, unlike the ones of LambdaWorks where it is a
+/// constant, so that the transforms of the library can be used with any modulus.
+#[derive(Debug, Clone)]
+struct RuntimeField;
+
+type FE = FieldElement<RuntimeField>;
+
+impl RuntimeField {
+    fn modulus() -> u64 {
+        MODULUS.with(Cell::get)
+    }
+}
+
+impl IsField for RuntimeField {
+    type BaseType = u64;
+
+    fn add(a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % Self::modulus() as u128) as u64
+    }
+
+    fn mul(a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % Self::modulus() as u128) as u64
+    }
+
+    fn sub(a: &u64, b: &u64) -> u64 {
+        Self::add(a, &Self::neg(b))
+    }
+
+    fn neg(a: &u64) -> u64 {
+        if *a == 0 {
+            0
+        } else {
+            Self::modulus() - a
+        }
+    }
+
+    fn inv(a: &u64) -> Result<u64, FieldError> {
+        if *a == 0 {
+            return Err(FieldError::InvZeroError);
+        }
+        // a^(q - 2) = a^-1 by Fermat's little theorem
+        Ok(Self::pow(a, Self::modulus() - 2))
+    }
+
+    fn div(a: &u64, b: &u64) -> u64 {
+        Self::mul(a, &Self::inv(b).expect("division by zero"))
+    }
+
+    fn eq(a: &u64, b: &u64) -> bool {
+        a == b
+    }
+
+    fn zero() -> u64 {
+        0
+    }
+
+    fn one() -> u64 {
+        1
+    }
+
+    fn from_u64(x: u64) -> u64 {
+        x % Self::modulus()
+    }
+
+    fn from_base_type(x: u64) -> u64 {
+        x % Self::modulus()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    Forward,
+    Inverse,
+    Mul,
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    modulus: u64,
+    size: Option<usize>,
+    input: Option<String>,
+    hex: bool,
+    json: bool,
+    values: Vec<String>,
+}
+
+/// Parses the command-line arguments, or returns `None` if help is requested.
+fn parse_args(args: &[String]) -> Result<Option<Args>, String> {
+    let mut args = args.iter();
+    let command = match args.next().map(String::as_str) {
+        Some("forward") => Command::Forward,
+        Some("inverse") => Command::Inverse,
+        Some("mul") => Command::Mul,
+        Some("-h" | "--help") => return Ok(None),
+        Some(other) => return Err(format!("unknown command: {}", other)),
+        None => return Err("missing command".to_string()),
+    };
+
+    let (mut modulus, mut size, mut input) = (None, None, None);
+    let (mut hex, mut json) = (false, false);
+    let mut values = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value_of = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("missing value for {}", name))
+        };
+        match arg.as_str() {
+            "-q" | "--modulus" => modulus = Some(parse_number(&value_of(arg)?)?),
+            "-n" | "--size" => size = Some(parse_number(&value_of(arg)?)? as usize),
+            "-i" | "--input" => input = Some(value_of(arg)?),
+            "-x" | "--hex" => hex = true,
+            "-j" | "--json" => json = true,
+            "-h" | "--help" => return Ok(None),
+            // negative numbers are values, not options
+            _ if arg.starts_with('-') && !arg[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                return Err(format!("unknown option: {}", arg))
+            }
+            _ => values.push(arg.clone()),
+        }
+    }
+
+    let modulus = modulus.ok_or("missing --modulus")?;
+    if input.is_some() && !values.is_empty() {
+        return Err("values are given both as arguments and with --input".to_string());
+    }
+
+    Ok(Some(Args {
+        command,
+        modulus,
+        size,
+        input,
+        hex,
+        json,
+        values,
+    }))
+}
+
+/// Parses a non-negative decimal or 0x-prefixed hexadecimal number.
+fn parse_number(s: &str) -> Result<u64, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|e| format!("invalid number {}: {}", s, e))
+}
+
+/// Parses a field element, which may be negative but must be below the modulus in absolute value.
+fn parse_value(s: &str) -> Result<FE, String> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+
+    let value = parse_number(digits)?;
+    if value >= RuntimeField::modulus() {
+        return Err(format!("value {} is not below the modulus", s));
+    }
+    let value = FE::from(value);
+    Ok(if negative { -value } else { value })
+}
+
+/// Parses the vectors within `text`, which is either a JSON array of numbers (or hexadecimal strings), a JSON
+/// array of such arrays, or CSV with a vector on each line.
+fn parse_vectors(text: &str) -> Result<Vec<Vec<FE>>, String> {
+    let text = text.trim();
+    if !text.starts_with('[') {
+        return text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(parse_value)
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn json_value(value: &serde_json::Value) -> Result<FE, String> {
+        match value {
+            serde_json::Value::Number(n) => parse_value(&n.to_string()),
+            serde_json::Value::String(s) => parse_value(s),
+            other => Err(format!("invalid value: {}", other)),
+        }
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+    let array = json.as_array().ok_or("JSON input must be an array")?;
+    if array.iter().all(serde_json::Value::is_array) && !array.is_empty() {
+        array
+            .iter()
+            .map(|vector| vector.as_array().unwrap().iter().map(json_value).collect())
+            .collect()
+    } else {
+        Ok(vec![array
+            .iter()
+            .map(json_value)
+            .collect::<Result<_, _>>()?])
+    }
+}
+
+/// Deterministic Miller-Rabin test, which is exact for all 64-bit numbers with these bases.
+fn is_prime(q: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if q < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| q.is_multiple_of(p)) {
+        return q == p;
+    }
+
+    let mul_mod = |a: u64, b: u64| ((a as u128 * b as u128) % q as u128) as u64;
+    let pow_mod = |mut a: u64, mut e: u64| {
+        let mut res = 1;
+        while e > 0 {
+            if e & 1 == 1 {
+                res = mul_mod(res, a);
+            }
+            a = mul_mod(a, a);
+            e >>= 1;
+        }
+        res
+    };
+
+    // q - 1 = d . 2^s with odd d
+    let s = (q - 1).trailing_zeros();
+    let d = (q - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d);
+        if x == 1 || x == q - 1 {
+            return true;
+        }
+        (1..s).any(|_| {
+            x = mul_mod(x, x);
+            x == q - 1
+        })
+    })
+}
+
+/// Returns the distinct prime factors of `n`.
+fn prime_factors(mut n: usize) -> Vec<usize> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// Returns the smallest size of at least `len` that a transform exists for modulo `q`, i.e. a divisor of `q - 1`
+/// without prime factors larger than `MAX_PRIME_RADIX`. These are the divisors of the part of `q - 1` made of
+/// the small primes, so there are few of them even when `q - 1` has a large prime factor.
+fn smallest_size(q: u64, len: usize) -> Option<usize> {
+    let mut rest = q - 1;
+    let mut sizes = vec![1];
+    for p in 2..=MAX_PRIME_RADIX as u64 {
+        // a composite p does not divide the rest, as its prime factors are removed already
+        let mut powers = vec![1];
+        while rest.is_multiple_of(p) {
+            rest /= p;
+            powers.push(powers.last().unwrap() * p);
+        }
+        sizes = sizes
+            .iter()
+            .flat_map(|&n| powers.iter().map(move |&p_k| n * p_k))
+            .collect();
+    }
+    sizes
+        .into_iter()
+        .filter(|&n| n >= len as u64)
+        .min()
+        .map(|n| n as usize)
+}
+
+/// Finds a primitive `n`-th root of unity via cofactor clearing: for `x = 2, 3, ...`, the element
+/// `w = x^((q - 1) / n)` is an `n`-th root, which is primitive if `w^(n/p) != 1` for every prime factor `p` of `n`.
+fn root_of_unity(n: usize) -> Result<FE, String> {
+    let q = RuntimeField::modulus();
+    if !(q - 1).is_multiple_of(n as u64) {
+        return Err(format!("size {} does not divide q - 1 = {}", n, q - 1));
+    }
+
+    let factors = prime_factors(n);
+    (1..q)
+        .map(|x| FE::from(x).pow((q - 1) / n as u64))
+        .find(|w| factors.iter().all(|&p| w.pow((n / p) as u64) != FE::one()))
+        .ok_or_else(|| format!("no primitive root of unity of order {}", n))
+}
+
+/// Transform of the given size: the radix-2 one for powers of two, and the mixed-radix one otherwise.
+enum Transform {
+    Radix2(NTT<RuntimeField>),
+    MixedRadix(MixedRadixNTT<RuntimeField>),
+}
+
+impl Transform {
+    fn new(n: usize) -> Result<Self, String> {
+        if n == 0 {
+            return Err("size must be positive".to_string());
+        }
+        if let Some(p) = prime_factors(n).into_iter().find(|&p| p > MAX_PRIME_RADIX) {
+            return Err(format!(
+                "size {} has the prime factor {} larger than {}",
+                n, p, MAX_PRIME_RADIX
+            ));
+        }
+
+        let w = root_of_unity(n)?;
+        log::debug!("primitive {}-th root of unity: {}", n, w.value());
+        Ok(if n > 1 && n.is_power_of_two() {
+            Self::Radix2(NTT::new(w, n as u64))
+        } else {
+            Self::MixedRadix(MixedRadixNTT::new(w, n as u64))
+        })
+    }
+
+    fn forward(&self, values: &[FE]) -> Vec<FE> {
+        match self {
+            Self::Radix2(ntt) => ntt.forward(values),
+            Self::MixedRadix(ntt) => ntt.forward(values),
+        }
+    }
+
+    fn inverse(&self, values: &[FE]) -> Vec<FE> {
+        match self {
+            Self::Radix2(ntt) => ntt.inverse(values),
+            Self::MixedRadix(ntt) => ntt.inverse(values),
+        }
+    }
+}
+
+/// Pads `values` with zeros to size `n`.
+fn pad(mut values: Vec<FE>, n: usize) -> Result<Vec<FE>, String> {
+    if values.len() > n {
+        return Err(format!("{} values do not fit in size {}", values.len(), n));
+    }
+    values.resize(n, FE::zero());
+    Ok(values)
+}
+
+/// Runs the command with the arguments, reading the values from `stdin` if they are not given otherwise, and
+/// returns the output to print.
+fn run(args: &Args, stdin: &mut dyn Read) -> Result<String, String> {
+    let q = args.modulus;
+    if !is_prime(q) {
+        return Err(format!("modulus {} is not a prime", q));
+    }
+    MODULUS.with(|modulus| modulus.set(q));
+
+    let text = if let Some(path) = &args.input {
+        fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?
+    } else if !args.values.is_empty() {
+        // a vector per argument for mul, and a single one otherwise
+        let separator = if args.command == Command::Mul {
+            "\n"
+        } else {
+            " "
+        };
+        args.values.join(separator)
+    } else {
+        let mut text = String::new();
+        stdin
+            .read_to_string(&mut text)
+            .map_err(|e| format!("could not read the standard input: {}", e))?;
+        text
+    };
+    let vectors = parse_vectors(&text)?;
+
+    let result = match args.command {
+        Command::Forward | Command::Inverse => {
+            let values = vectors.concat();
+            let n = args.size.unwrap_or(values.len());
+            let transform = Transform::new(n)?;
+            let values = pad(values, n)?;
+            if args.command == Command::Forward {
+                transform.forward(&values)
+            } else {
+                transform.inverse(&values)
+            }
+        }
+        Command::Mul => {
+            let [a, b] = <[Vec<FE>; 2]>::try_from(vectors)
+                .map_err(|vectors| format!("mul takes 2 vectors, got {}", vectors.len()))?;
+            if a.is_empty() || b.is_empty() {
+                return Err("mul takes non-empty vectors".to_string());
+            }
+
+            // without a size, the smallest one that holds the product, so that the convolution does not wrap around
+            let len = a.len() + b.len() - 1;
+            let n = match args.size {
+                Some(n) => n,
+                None => smallest_size(q, len)
+                    .ok_or(format!("no transform size for a product of size {}", len))?,
+            };
+            let transform = Transform::new(n)?;
+
+            let a_evals = transform.forward(&pad(a, n)?);
+            let b_evals = transform.forward(&pad(b, n)?);
+            let evals = a_evals
+                .iter()
+                .zip(&b_evals)
+                .map(|(a, b)| a * b)
+                .collect::<Vec<_>>();
+            let mut product = transform.inverse(&evals);
+            if args.size.is_none() {
+                product.truncate(len);
+            }
+            product
+        }
+    };
+
+    Ok(format_values(&result, args.hex, args.json))
+}
+
+/// Formats the values in decimal or hexadecimal, as comma-separated values or as a JSON array.
+fn format_values(values: &[FE], hex: bool, json: bool) -> String {
+    let formatted = values.iter().map(|value| {
+        if hex {
+            format!("{:#x}", value.value())
+        } else {
+            value.value().to_string()
+        }
+    });
+
+    if json {
+        let array = formatted
+            .map(|s| if hex { format!("\"{}\"", s) } else { s })
+            .collect::<Vec<_>>();
+        format!("[{}]", array.join(","))
+    } else {
+        formatted.collect::<Vec<_>>().join(",")
+    }
+}
 
 fn main() {
-    env::set_var("RUST_LOG", "debug");
     env_logger::init();
 
-    // 13 is a primitive 4-th root of unity
-    // and 4 is the max we can have here because 17 - 1 = 2^4
-    let w = FE::from(13u64);
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = match parse_args(&args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    match run(&args, &mut io::stdin()) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let coeffs = (0..4).map(|i| FE::from(i as u64)).collect::<Vec<_>>();
-    let poly = Polynomial::new(&coeffs);
-    let ntt = NTT::new(w.clone(), 4);
+    /// Runs the command line `cmd` with `stdin`, and returns its output.
+    fn run_cmd(cmd: &str, stdin: &str) -> Result<String, String> {
+        let args = cmd.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let args = parse_args(&args)?.expect("not a help request");
+        run(&args, &mut stdin.as_bytes())
+    }
+
+    #[test]
+    fn test_forward_inverse() {
+        // with the primitive 4-th root 13 modulo 17
+        assert_eq!(run_cmd("forward -q 17 0 1 2 3", ""), Ok("6,6,15,7".into()));
+        assert_eq!(run_cmd("inverse -q 17 6,6,15,7", ""), Ok("0,1,2,3".into()));
+        assert_eq!(
+            run_cmd("forward --modulus 0x11 --hex --json", "[0, 1, 2, 3]"),
+            Ok(r#"["0x6","0x6","0xf","0x7"]"#.into())
+        );
 
-    log::info!("Twiddle factors:");
-    for (i, w_i) in ntt.twiddles.iter().enumerate() {
-        log::info!("w^{} = {}", i, w_i.representative());
+        // padded to size 8, and a mixed-radix one of size 3 . 2^k over the Stark101 field
+        let evals = run_cmd("forward -q 17 -n 8 0 1 2 3", "").unwrap();
+        assert_eq!(
+            run_cmd(&format!("inverse -q 17 {}", evals), ""),
+            Ok("0,1,2,3,0,0,0,0".into())
+        );
+        let evals = run_cmd("forward -q 3221225473 -n 12", "1\n2\n3\n-1\n").unwrap();
+        assert_eq!(
+            run_cmd(&format!("inverse -q 3221225473 {}", evals), ""),
+            Ok("1,2,3,3221225472,0,0,0,0,0,0,0,0".into())
+        );
     }
-    let evals = ntt.forward(&coeffs);
 
-    // confirm evaluations
-    for (i, e) in evals.iter().enumerate() {
-        let y = poly.evaluate(&ntt.twiddles[i]);
-        assert_eq!(e, &y);
-        log::debug!("A_{} = {}", i, e.representative());
+    #[test]
+    fn test_mul() {
+        // (1 + 2x + 3x^2)(4 + 5x) = 4 + 13x + 22x^2 + 15x^3
+        assert_eq!(
+            run_cmd("mul -q 3329 1,2,3 4,5", ""),
+            Ok("4,13,22,15".into())
+        );
+        assert_eq!(
+            run_cmd("mul -q 3329", "1,2,3\n4,5\n"),
+            Ok("4,13,22,15".into())
+        );
+        assert_eq!(
+            run_cmd("mul -q 3329 -j", "[[1,2,3],[4,5]]"),
+            Ok("[4,13,22,15]".into())
+        );
+
+        // modulo x^n - 1 with a given size, e.g. (1 + 2x)(4 + 5x) = 4 + 13x + 10x^2 = 14 + 13x modulo x^2 - 1
+        assert_eq!(
+            run_cmd("mul -q 3329 -n 2 1,2,3 4,5", ""),
+            Err("3 values do not fit in size 2".into())
+        );
+        assert_eq!(run_cmd("mul -q 3329 -n 2 1,2 4,5", ""), Ok("14,13".into()));
+        assert_eq!(run_cmd("mul -q 3329 -n 2 1 4,5", ""), Ok("4,5".into()));
+        assert_eq!(
+            run_cmd("mul -q 3329 -n 4 1,2,3 4,5", ""),
+            Ok("4,13,22,15".into())
+        );
+        assert_eq!(run_cmd("mul -q 3329 -n 2 0,1 0,1", ""), Ok("1,0".into()));
+
+        // over the Stark101 field, where q - 1 = 3 . 2^30, the smallest size for a product of size 3 is 3 itself
+        assert_eq!(smallest_size(3221225473, 3), Some(3));
+        assert_eq!(smallest_size(3221225473, 5), Some(6));
+        assert_eq!(smallest_size(17, 5), Some(8));
+        assert_eq!(
+            run_cmd("mul -q 3221225473 1,2 3,4", ""),
+            Ok("3,10,8".into())
+        );
     }
 
-    // inverse
-    // TODO: !!!
+    #[test]
+    fn test_errors() {
+        assert!(run_cmd("forward -q 15 1 2", "")
+            .unwrap_err()
+            .contains("not a prime"));
+        assert!(run_cmd("forward -q 17 1 2 3", "")
+            .unwrap_err()
+            .contains("does not divide"));
+        assert!(run_cmd("forward -q 17 1 17", "")
+            .unwrap_err()
+            .contains("not below"));
+        assert!(run_cmd("forward -q 47 -n 23", "")
+            .unwrap_err()
+            .contains("prime factor 23"));
+        assert!(run_cmd("forward 1 2", "")
+            .unwrap_err()
+            .contains("--modulus"));
+        assert!(run_cmd("transform -q 17", "")
+            .unwrap_err()
+            .contains("unknown command"));
+        assert!(run_cmd("mul -q 17 1,2", "")
+            .unwrap_err()
+            .contains("2 vectors"));
+        // q - 1 = 2 . 1099511627933 for this safe prime, so there is no size of at least 5
+        assert!(run_cmd("mul -q 2199023255867 1,2,3 4,5,6", "")
+            .unwrap_err()
+            .contains("no transform size"));
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes = [2, 3, 17, 3329, 8380417, 3221225473, 0xffffffff00000001];
+        assert!(primes.iter().all(|&p| is_prime(p)));
+        let composites = [0, 1, 4, 15, 3329 * 17, 0xffffffff00000003, u64::MAX];
+        assert!(composites.iter().all(|&n| !is_prime(n)));
+    }
 }
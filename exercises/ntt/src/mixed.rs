@@ -0,0 +1,489 @@
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::{IsFFTField, IsField},
+};
+
+use crate::NTT;
+
+/// Largest prime factor of `n` that the mixed-radix transform handles with a butterfly of its own; lengths with
+/// larger prime factors go through Bluestein's algorithm instead.
+pub const MAX_PRIME_RADIX: usize = 13;
+
+/// Mixed-radix NTT of any size `n` whose prime factors are at most `MAX_PRIME_RADIX`, e.g. `n = 3 . 2^k`.
+///
+/// With `n = r . m`, the inputs are split into `r` decimated sequences `x_s = (a_s, a_(s+r), a_(s+2r), ...)` of
+/// size `m`, each transformed to `X_s` recursively, and the outputs are combined as:
+///
+/// `A_(j + qm) = sum_s w_n^(sj) . X_s[j] . w_r^(sq)`
+///
+/// for `j = 0..m` and `q = 0..r`, which is a radix-`r` butterfly on the inputs `w_n^(sj) . X_s[j]`, where
+/// `w_r = w_n^m` is a primitive `r`-th root. The radix-2 case is the usual Cooley-Tukey butterfly.
+pub struct MixedRadixNTT<F: IsField> {
+    /// Twiddle factors w^0, w^1, w^2, ..., w^(n-1) in order,
+    /// so that `twiddles[i] = w^i`.
+    pub twiddles: Vec<FieldElement<F>>,
+    /// Inverse twiddle factors w^0, w^-1, w^-2, ..., w^-(n-1) in order,
+    /// so that `inv_twiddles[i] = w^-i`.
+    pub inv_twiddles: Vec<FieldElement<F>>,
+    /// Radix of each level of the recursion, with product `n`.
+    radices: Vec<usize>,
+}
+
+impl<F: IsField> MixedRadixNTT<F> {
+    /// Creates the transform of size `n` with the primitive `n`-th root `w`.
+    ///
+    /// ## Panics
+    ///
+    /// If `n` has a prime factor larger than `MAX_PRIME_RADIX`, or `w` is not a primitive `n`-th root.
+    pub fn new(w: FieldElement<F>, n: u64) -> Self {
+        let radices = radices(n as usize).expect("n must not have large prime factors");
+        assert_primitive(&w, n);
+
+        // w^-1 = w^(n-1) as w^n = 1
+        let w_inv = w.pow(n - 1);
+        Self {
+            twiddles: powers(&w, n as usize),
+            inv_twiddles: powers(&w_inv, n as usize),
+            radices,
+        }
+    }
+
+    /// Evaluates the polynomial with `coeffs` at the `n`-th roots of unity, i.e. returns `A_j = p(w^j)`.
+    pub fn forward(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        assert_eq!(coeffs.len(), self.twiddles.len(), "size mismatch");
+        transform(coeffs, &self.radices, &self.twiddles)
+    }
+
+    /// Interpolates the evaluations `evals` at the `n`-th roots of unity back to the coefficients, i.e. the
+    /// inverse of `forward`, which is the same transform with the inverse twiddles scaled by `1/n`.
+    pub fn inverse(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        assert_eq!(evals.len(), self.inv_twiddles.len(), "size mismatch");
+        let n_inv = FieldElement::<F>::from(evals.len() as u64)
+            .inv()
+            .expect("n must be invertible");
+
+        transform(evals, &self.radices, &self.inv_twiddles)
+            .into_iter()
+            .map(|value| value * &n_inv)
+            .collect()
+    }
+}
+
+/// NTT of any size `n` dividing `p - 1`, with Bluestein's algorithm.
+///
+/// Using `ij = C(i + j, 2) - C(i, 2) - C(j, 2)` where `C(x, 2) = x(x - 1)/2`, the transform becomes:
+///
+/// `A_j = w^(-C(j, 2)) . sum_i (a_i . w^(-C(i, 2))) . w^(C(i + j, 2))`
+///
+/// which is a convolution of the weighted inputs (in reverse order) with the "chirp" `w^C(t, 2)` for
+/// `t = 0..2n-1`. It is computed with a power-of-two NTT of size `M >= 2n - 1`, so that the wrap-around of the
+/// cyclic convolution does not reach the `n` outputs we need; the field must have a two-adicity of at least
+/// `log2(M)`. This only needs a primitive `n`-th root, unlike the usual form with `ij = (i^2 + j^2 - (i - j)^2) / 2`
+/// that needs a `2n`-th one.
+pub struct BluesteinNTT<F: IsFFTField> {
+    /// Power-of-two NTT for the convolutions.
+    conv: NTT<F>,
+    /// Chirp of the forward transform, with `w`.
+    forward: Chirp<F>,
+    /// Chirp of the inverse transform, with `w^-1`.
+    inverse: Chirp<F>,
+}
+
+/// Precomputed values of Bluestein's algorithm for a primitive `n`-th root `w`.
+struct Chirp<F: IsField> {
+    /// `w^(-C(j, 2))` for `j = 0..n`.
+    weights: Vec<FieldElement<F>>,
+    /// NTT of `w^C(t, 2)` for `t = 0..2n-1`, padded with zeros to the size of the convolution.
+    kernel: Vec<FieldElement<F>>,
+}
+
+impl<F: IsFFTField> BluesteinNTT<F> {
+    /// Creates the transform of size `n` with the primitive `n`-th root `w`.
+    ///
+    /// ## Panics
+    ///
+    /// If `w` is not a primitive `n`-th root, or the two-adicity of the field is too small for the convolution.
+    pub fn new(w: FieldElement<F>, n: u64) -> Self {
+        assert_primitive(&w, n);
+
+        let size = (2 * n as usize - 1).next_power_of_two().max(2);
+        let root = F::get_primitive_root_of_unity(size.trailing_zeros() as u64)
+            .expect("convolution size exceeds the two-adicity of the field");
+        let conv = NTT::new(root, size as u64);
+
+        let w_inv = w.pow(n - 1);
+        Self {
+            forward: Chirp::new(&w, n as usize, &conv),
+            inverse: Chirp::new(&w_inv, n as usize, &conv),
+            conv,
+        }
+    }
+
+    /// Evaluates the polynomial with `coeffs` at the `n`-th roots of unity, i.e. returns `A_j = p(w^j)`.
+    pub fn forward(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        self.apply(coeffs, &self.forward)
+    }
+
+    /// Interpolates the evaluations `evals` at the `n`-th roots of unity back to the coefficients, i.e. the
+    /// inverse of `forward`.
+    pub fn inverse(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let n_inv = FieldElement::<F>::from(evals.len() as u64)
+            .inv()
+            .expect("n must be invertible");
+
+        self.apply(evals, &self.inverse)
+            .into_iter()
+            .map(|value| value * &n_inv)
+            .collect()
+    }
+
+    fn apply(&self, values: &[FieldElement<F>], chirp: &Chirp<F>) -> Vec<FieldElement<F>> {
+        let n = chirp.weights.len();
+        assert_eq!(values.len(), n, "size mismatch");
+
+        // weighted inputs in reverse order, so that the correlation becomes a convolution
+        let mut u = values
+            .iter()
+            .zip(&chirp.weights)
+            .rev()
+            .map(|(a, w)| a * w)
+            .collect::<Vec<_>>();
+        u.resize(chirp.kernel.len(), FieldElement::zero());
+
+        self.conv.forward_in_place(&mut u);
+        for (u_j, v_j) in u.iter_mut().zip(&chirp.kernel) {
+            *u_j = &*u_j * v_j;
+        }
+        self.conv.inverse_in_place(&mut u);
+
+        u[n - 1..2 * n - 1]
+            .iter()
+            .zip(&chirp.weights)
+            .map(|(u_j, w)| u_j * w)
+            .collect()
+    }
+}
+
+impl<F: IsField> Chirp<F> {
+    fn new(w: &FieldElement<F>, n: usize, conv: &NTT<F>) -> Self {
+        // exponents C(t, 2) mod n, using C(t + 1, 2) = C(t, 2) + t
+        let exponents = (0..2 * n - 1)
+            .scan(0, |e, t| {
+                let e_t = *e;
+                *e = (*e + t) % n;
+                Some(e_t)
+            })
+            .collect::<Vec<_>>();
+        let pows = powers(w, n);
+
+        let weights = exponents[..n]
+            .iter()
+            .map(|&e| pows[(n - e) % n].clone())
+            .collect();
+
+        let mut kernel = exponents
+            .iter()
+            .map(|&e| pows[e].clone())
+            .collect::<Vec<_>>();
+        kernel.resize(conv.size(), FieldElement::zero());
+        conv.forward_in_place(&mut kernel);
+
+        Self { weights, kernel }
+    }
+}
+
+/// NTT of any size `n` dividing `p - 1`, using the mixed-radix transform when the prime factors of `n` are at
+/// most `MAX_PRIME_RADIX`, and Bluestein's algorithm otherwise.
+pub enum NTTPlan<F: IsFFTField> {
+    MixedRadix(MixedRadixNTT<F>),
+    Bluestein(BluesteinNTT<F>),
+}
+
+impl<F: IsFFTField> NTTPlan<F> {
+    /// Plans the transform of size `n` with the primitive `n`-th root `w`.
+    pub fn new(w: FieldElement<F>, n: u64) -> Self {
+        if radices(n as usize).is_some() {
+            log::debug!("planning mixed-radix NTT of size {}", n);
+            Self::MixedRadix(MixedRadixNTT::new(w, n))
+        } else {
+            log::debug!("planning Bluestein NTT of size {}", n);
+            Self::Bluestein(BluesteinNTT::new(w, n))
+        }
+    }
+
+    /// Evaluates the polynomial with `coeffs` at the `n`-th roots of unity, i.e. returns `A_j = p(w^j)`.
+    pub fn forward(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        match self {
+            Self::MixedRadix(ntt) => ntt.forward(coeffs),
+            Self::Bluestein(ntt) => ntt.forward(coeffs),
+        }
+    }
+
+    /// Interpolates the evaluations `evals` at the `n`-th roots of unity back to the coefficients.
+    pub fn inverse(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        match self {
+            Self::MixedRadix(ntt) => ntt.inverse(evals),
+            Self::Bluestein(ntt) => ntt.inverse(evals),
+        }
+    }
+}
+
+/// Mixed-radix transform of `values` with the given `twiddles`, which are the powers of a primitive root of
+/// unity of order `twiddles.len()`; the sub-transforms use them at a stride, as in the radix-2 one.
+fn transform<F: IsField>(
+    values: &[FieldElement<F>],
+    radices: &[usize],
+    twiddles: &[FieldElement<F>],
+) -> Vec<FieldElement<F>> {
+    let n = values.len();
+    if n == 1 {
+        return values.to_vec();
+    }
+
+    let r = radices[0];
+    let m = n / r;
+    let stride = twiddles.len() / n;
+    let subs = (0..r)
+        .map(|s| {
+            let decimated = values
+                .iter()
+                .skip(s)
+                .step_by(r)
+                .cloned()
+                .collect::<Vec<_>>();
+            transform(&decimated, &radices[1..], twiddles)
+        })
+        .collect::<Vec<_>>();
+
+    let mut res = vec![FieldElement::zero(); n];
+    let mut t = Vec::with_capacity(r);
+    for j in 0..m {
+        // t_s = w_n^(sj) . X_s[j]
+        t.clear();
+        t.extend((0..r).map(|s| &twiddles[s * j * stride] * &subs[s][j]));
+
+        // w_r = w_n^m is at a stride of twiddles.len() / r
+        butterfly(&mut t, twiddles, twiddles.len() / r);
+        for (q, t_q) in t.drain(..).enumerate() {
+            res[j + q * m] = t_q;
+        }
+    }
+
+    res
+}
+
+/// Radix-`r` butterfly on `r = t.len()` values in-place, i.e. the transform `y_q = sum_s t_s . w_r^(sq)` where
+/// `w_r = twiddles[stride]` is a primitive `r`-th root of unity.
+fn butterfly<F: IsField>(t: &mut [FieldElement<F>], twiddles: &[FieldElement<F>], stride: usize) {
+    match t.len() {
+        2 => {
+            let b = t[1].clone();
+            t[1] = &t[0] - &b;
+            t[0] = &t[0] + b;
+        }
+        3 => {
+            // with 1 + w + w^2 = 0, we have:
+            // y_1 = t_0 + w t_1 + w^2 t_2 = t_0 - t_2 + w (t_1 - t_2)
+            // y_2 = t_0 + w^2 t_1 + w t_2 = t_0 - t_1 - w (t_1 - t_2)
+            let u = &twiddles[stride] * (&t[1] - &t[2]);
+            let y_0 = &t[0] + &t[1] + &t[2];
+            let y_1 = &t[0] - &t[2] + &u;
+            let y_2 = &t[0] - &t[1] - u;
+            (t[0], t[1], t[2]) = (y_0, y_1, y_2);
+        }
+        4 => {
+            // w is a square root of -1, so this is two radix-2 layers:
+            // y_0 = (t_0 + t_2) + (t_1 + t_3), y_2 = (t_0 + t_2) - (t_1 + t_3)
+            // y_1 = (t_0 - t_2) + w (t_1 - t_3), y_3 = (t_0 - t_2) - w (t_1 - t_3)
+            let a = &t[0] + &t[2];
+            let b = &t[0] - &t[2];
+            let c = &t[1] + &t[3];
+            let d = &twiddles[stride] * (&t[1] - &t[3]);
+            (t[0], t[1], t[2], t[3]) = (&a + &c, &b + &d, a - c, b - d);
+        }
+        r => {
+            // naive transform for the other small primes
+            let y = (0..r)
+                .map(|q| {
+                    t.iter()
+                        .enumerate()
+                        .fold(FieldElement::zero(), |acc, (s, t_s)| {
+                            acc + &twiddles[(s * q % r) * stride] * t_s
+                        })
+                })
+                .collect::<Vec<_>>();
+            t.clone_from_slice(&y);
+        }
+    }
+}
+
+/// Returns the radices of the mixed-radix transform of size `n`, or `None` if `n` has a prime factor larger
+/// than `MAX_PRIME_RADIX`. Pairs of 2s are merged into radix-4 levels.
+fn radices(n: usize) -> Option<Vec<usize>> {
+    let factors = prime_factors(n);
+    if factors.iter().any(|&q| q > MAX_PRIME_RADIX) {
+        return None;
+    }
+
+    let twos = factors.iter().filter(|&&q| q == 2).count();
+    let mut radices = vec![4; twos / 2];
+    if twos % 2 == 1 {
+        radices.push(2);
+    }
+    radices.extend(factors.into_iter().filter(|&q| q != 2));
+    Some(radices)
+}
+
+/// Returns the prime factors of `n` in increasing order, with multiplicity.
+fn prime_factors(mut n: usize) -> Vec<usize> {
+    let mut factors = Vec::new();
+    let mut q = 2;
+    while q * q <= n {
+        while n.is_multiple_of(q) {
+            factors.push(q);
+            n /= q;
+        }
+        q += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// Asserts that `w` is a primitive `n`-th root of unity, i.e. `w^n = 1` and `w^(n/q) != 1` for every prime
+/// factor `q` of `n`.
+fn assert_primitive<F: IsField>(w: &FieldElement<F>, n: u64) {
+    assert!(n > 0, "n must be positive");
+    assert_eq!(w.pow(n), FieldElement::one(), "w must be an n-th root");
+    for q in prime_factors(n as usize) {
+        assert_ne!(
+            w.pow(n / q as u64),
+            FieldElement::one(),
+            "w must be primitive"
+        );
+    }
+}
+
+/// Returns `w^0, w^1, ..., w^(n-1)`.
+fn powers<F: IsField>(w: &FieldElement<F>, n: usize) -> Vec<FieldElement<F>> {
+    std::iter::successors(Some(FieldElement::one()), |w_i| Some(w_i * w))
+        .take(n)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lambdaworks_math::{
+        elliptic_curve::short_weierstrass::curves::bls12_381::default_types::FrField,
+        field::fields::{
+            fft_friendly::babybear::Babybear31PrimeField, u64_prime_field::U64PrimeField,
+        },
+        polynomial::Polynomial,
+        unsigned_integer::element::UnsignedInteger,
+    };
+
+    fn random_coeffs<F: IsField>(n: usize) -> Vec<FieldElement<F>> {
+        (0..n)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect()
+    }
+
+    /// Returns `g^((p - 1) / n)`, which is a primitive `n`-th root when `g` generates the multiplicative group.
+    fn root_of_unity<F: IsField>(g: u64, p_minus_one: u64, n: u64) -> FieldElement<F> {
+        assert_eq!(p_minus_one % n, 0, "n must divide p - 1");
+        FieldElement::<F>::from(g).pow(p_minus_one / n)
+    }
+
+    /// Checks `forward` against polynomial evaluation at the powers of `w`, and the round trip with `inverse`.
+    fn check<F: IsField>(
+        w: &FieldElement<F>,
+        n: usize,
+        forward: impl Fn(&[FieldElement<F>]) -> Vec<FieldElement<F>>,
+        inverse: impl Fn(&[FieldElement<F>]) -> Vec<FieldElement<F>>,
+    ) {
+        let coeffs = random_coeffs::<F>(n);
+        let evals = forward(&coeffs);
+        let poly = Polynomial::new(&coeffs);
+        for (j, eval) in evals.iter().enumerate() {
+            assert_eq!(
+                *eval,
+                poly.evaluate(&w.pow(j as u64)),
+                "n = {}, j = {}",
+                n,
+                j
+            );
+        }
+        assert_eq!(inverse(&evals), coeffs);
+    }
+
+    #[test]
+    fn test_radices() {
+        assert_eq!(radices(1), Some(vec![]));
+        assert_eq!(radices(2), Some(vec![2]));
+        assert_eq!(radices(8), Some(vec![4, 2]));
+        assert_eq!(radices(48), Some(vec![4, 4, 3]));
+        assert_eq!(radices(2 * 5 * 7 * 13), Some(vec![2, 5, 7, 13]));
+        assert_eq!(radices(19), None);
+        assert_eq!(radices(4 * 17), None);
+    }
+
+    #[test]
+    fn test_mixed_radix_stark101() {
+        // 5 generates the multiplicative group, with p - 1 = 3 . 2^30
+        type F = U64PrimeField<3221225473>;
+        for n in [1, 2, 3, 4, 6, 8, 12, 24, 48, 96, 192, 3 << 7] {
+            let w = root_of_unity::<F>(5, 3 << 30, n);
+            let ntt = MixedRadixNTT::new(w, n);
+            check(&w, n as usize, |x| ntt.forward(x), |x| ntt.inverse(x));
+        }
+    }
+
+    #[test]
+    fn test_mixed_radix_babybear() {
+        // 31 generates the multiplicative group, with p - 1 = 2^27 . 3 . 5
+        type F = Babybear31PrimeField;
+        for n in [5, 10, 15, 30, 60, 120, 240] {
+            let w = root_of_unity::<F>(31, 15 << 27, n);
+            let ntt = MixedRadixNTT::new(w.clone(), n);
+            check(&w, n as usize, |x| ntt.forward(x), |x| ntt.inverse(x));
+        }
+    }
+
+    #[test]
+    fn test_bluestein_babybear() {
+        type F = Babybear31PrimeField;
+        for n in [1, 2, 3, 5, 15, 16, 30, 60] {
+            let w = root_of_unity::<F>(31, 15 << 27, n);
+            let ntt = BluesteinNTT::new(w.clone(), n);
+            check(&w, n as usize, |x| ntt.forward(x), |x| ntt.inverse(x));
+        }
+    }
+
+    #[test]
+    fn test_plan_bls12_381() {
+        // 7 generates the multiplicative group of the scalar field, where r - 1 = 2^32 . 3 . 11 . 19 . 10177 ...
+        let p_minus_one = (-FieldElement::<FrField>::one()).representative();
+        for n in [19u64, 2 * 19, 11 * 19, 8 * 3 * 11, 10177] {
+            let (cofactor, rem) = p_minus_one.div_rem(&UnsignedInteger::from_u64(n));
+            assert_eq!(rem, UnsignedInteger::from_u64(0), "n must divide r - 1");
+            let w = FieldElement::<FrField>::from(7).pow(cofactor);
+
+            let plan = NTTPlan::new(w.clone(), n);
+            assert_eq!(
+                matches!(plan, NTTPlan::MixedRadix(_)),
+                n == 8 * 3 * 11,
+                "n = {}",
+                n
+            );
+            if n > 1000 {
+                // naive evaluation is quadratic, so we only check the round trip
+                let coeffs = random_coeffs::<FrField>(n as usize);
+                assert_eq!(plan.inverse(&plan.forward(&coeffs)), coeffs);
+            } else {
+                check(&w, n as usize, |x| plan.forward(x), |x| plan.inverse(x));
+            }
+        }
+    }
+}
@@ -1,9 +1,24 @@
-use lambdaworks_math::field::{element::FieldElement, traits::IsField};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::{IsFFTField, IsField},
+};
 
 pub struct NTT<F: IsField> {
-    /// Twiddle factors w^0, w^1, w^2, ..., w^(n-1) in order,
-    /// so that `twiddles[i] = w^i`.
+    /// Twiddle factors w^0, w^1, w^2, ..., w^(n/2-1) in bit-reversed order,
+    /// so that `twiddles[i] = w^rev(i)` where `rev` reverses the `log(n/2)` bits of `i`.
+    ///
+    /// The other half is not needed as `w^(i + n/2) = -w^i`, and in this order the first `m/2` twiddles are
+    /// the ones of a transform of size `m`, for any power of two `m <= n`.
     pub twiddles: Vec<FieldElement<F>>,
+    /// Inverse twiddle factors w^0, w^-1, w^-2, ..., w^-(n/2-1) in bit-reversed order,
+    /// so that `inv_twiddles[i] = w^-rev(i)`.
+    pub inv_twiddles: Vec<FieldElement<F>>,
 }
 
 impl<F: IsField> NTT<F> {
@@ -11,39 +26,392 @@ impl<F: IsField> NTT<F> {
         // must be primitive 2^k-th root of unity
         assert_eq!(n.count_ones(), 1, "n must be a power of 2");
         assert_eq!(w.pow(n), FieldElement::one());
-        assert_ne!(w.pow(n - 1), FieldElement::one());
+        assert_ne!(w.pow(n / 2), FieldElement::one(), "w must be primitive");
 
+        // w^-1 = w^(n-1) as w^n = 1
+        let w_inv = w.pow(n - 1);
         Self {
-            // construct twiddle factors w^0, w^1, w^2, ..., w^(n-1)
-            twiddles: (0..n).map(|i| w.pow(i)).collect(),
+            // construct twiddle factors w^0, w^1, w^2, ..., w^(n/2-1)
+            twiddles: bit_reversed_powers(&w, n as usize / 2),
+            // and their inverses w^0, w^-1, w^-2, ..., w^-(n/2-1)
+            inv_twiddles: bit_reversed_powers(&w_inv, n as usize / 2),
+        }
+    }
+
+    /// Returns the size `n` of the largest transform, i.e. the order of `w`.
+    pub fn size(&self) -> usize {
+        self.twiddles.len() << 1
+    }
+
+    /// Returns `w^i`, using `w^(i + n/2) = -w^i` for the half that is not in the table.
+    pub fn twiddle(&self, i: usize) -> FieldElement<F> {
+        let half = self.twiddles.len();
+        let i = i % (half << 1);
+        if i < half {
+            self.twiddles[reverse_bits(i, half)].clone()
+        } else {
+            -&self.twiddles[reverse_bits(i - half, half)]
         }
     }
 
+    /// Evaluates the polynomial with `coeffs` at the `n`-th roots of unity, where `n` is the number of coefficients,
+    /// i.e. returns `A_j = p(w_n^j)` for `j = 0..n` where `w_n` is the primitive `n`-th root within the twiddles.
     pub fn forward(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
-        assert!(self.twiddles.len() >= coeffs.len(), "too many inputs");
-        let n = coeffs.len();
+        let mut values = coeffs.to_vec();
+        self.forward_in_place(&mut values);
+        values
+    }
+
+    /// Same as `forward`, with the recursive algorithm that splits the coefficients into even and odd ones at
+    /// each level, as described in the README.
+    pub fn forward_recursive(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        self.transform(coeffs)
+    }
+
+    /// Same as `forward` but in-place, with the iterative Cooley-Tukey algorithm (decimation-in-time).
+    pub fn forward_in_place(&self, values: &mut [FieldElement<F>]) {
+        dit(values, &self.twiddles);
+        bit_reverse_permute(values);
+    }
+
+    /// Same as `forward` but in-place, with the iterative Gentleman-Sande algorithm (decimation-in-frequency).
+    pub fn forward_in_place_dif(&self, values: &mut [FieldElement<F>]) {
+        bit_reverse_permute(values);
+        dif(values, &self.twiddles);
+    }
+
+    /// Interpolates the evaluations `evals` at the `n`-th roots of unity back to the coefficients, i.e. the inverse
+    /// of `forward`. This is the same transform with the inverse twiddles, scaled by `1/n`:
+    ///
+    /// `a_i = 1/n * sum_j A_j * w_n^(-ij)`
+    pub fn inverse(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let mut values = evals.to_vec();
+        self.inverse_in_place(&mut values);
+        values
+    }
+
+    /// Same as `inverse` but in-place, with the iterative Gentleman-Sande algorithm (decimation-in-frequency).
+    pub fn inverse_in_place(&self, values: &mut [FieldElement<F>]) {
+        let n_inv = FieldElement::<F>::from(values.len() as u64)
+            .inv()
+            .expect("n must be invertible");
+
+        bit_reverse_permute(values);
+        dif(values, &self.inv_twiddles);
+        for value in values.iter_mut() {
+            *value = &*value * &n_inv;
+        }
+    }
+
+    /// Evaluates the polynomial with `coeffs` over the coset `h<w_n>` where `h` is the `offset`, i.e. returns
+    /// `A_j = p(h w_n^j)`. This is the transform of the coefficients `a_i h^i`, as `p(hx) = sum_i (a_i h^i) x^i`.
+    pub fn forward_coset(
+        &self,
+        coeffs: &[FieldElement<F>],
+        offset: &FieldElement<F>,
+    ) -> Vec<FieldElement<F>> {
+        let mut values = coeffs.to_vec();
+        scale_by_powers(&mut values, offset);
+        self.forward_in_place(&mut values);
+        values
+    }
+
+    /// Interpolates the evaluations `evals` over the coset `h<w_n>` where `h` is the `offset` back to the
+    /// coefficients, i.e. the inverse of `forward_coset`, which undoes the scaling with `h^-i`.
+    pub fn inverse_coset(
+        &self,
+        evals: &[FieldElement<F>],
+        offset: &FieldElement<F>,
+    ) -> Vec<FieldElement<F>> {
+        let mut values = self.inverse(evals);
+        scale_by_powers(&mut values, &offset.inv().expect("offset must not be zero"));
+        values
+    }
+
+    /// Negacyclic NTT of `coeffs` over `Z_q[X]/(X^n + 1)`, i.e. the evaluations `A_j = p(psi^(2j + 1))` at the
+    /// roots of `X^n + 1`, where `psi` is a primitive `2n`-th root of unity. Multiplying these pointwise multiplies
+    /// the polynomials modulo `X^n + 1`, instead of `X^n - 1` as with `forward`.
+    ///
+    /// This is the transform over the coset `psi<psi^2>`, so the NTT must have a size of at least `2n`.
+    pub fn forward_negacyclic(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        self.forward_coset(coeffs, &self.negacyclic_root(coeffs.len()))
+    }
+
+    /// Interpolates the evaluations `evals` at the roots of `X^n + 1` back to the coefficients, i.e. the inverse
+    /// of `forward_negacyclic`.
+    pub fn inverse_negacyclic(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        self.inverse_coset(evals, &self.negacyclic_root(evals.len()))
+    }
+
+    /// Incomplete negacyclic NTT of `coeffs` over `Z_q[X]/(X^n + 1)`, as in Kyber where `q = 3329` has
+    /// primitive `n`-th roots of unity but no `2n`-th roots for `n = 256`.
+    ///
+    /// With `p(X) = E(X^2) + X O(X^2)`, the halves `E` and `O` are in `Z_q[Y]/(Y^(n/2) + 1)` for `Y = X^2`, so
+    /// their negacyclic transforms of size `n/2` only need a primitive `n`-th root `psi`. The output holds the pairs
+    /// `(E(g_j), O(g_j))` for `g_j = psi^(2j + 1)`, which is `p` modulo `X^2 - g_j`; these are multiplied with
+    /// `mul_incomplete`. The NTT must have a size of at least `n`.
+    pub fn forward_incomplete(&self, coeffs: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        assert_eq!(coeffs.len() % 2, 0, "n must be even");
+        let (even, odd) = even_odd_split(coeffs.to_vec());
+        interleave(
+            self.forward_negacyclic(&even),
+            self.forward_negacyclic(&odd),
+        )
+    }
+
+    /// Interpolates the pairs of `forward_incomplete` back to the coefficients.
+    pub fn inverse_incomplete(&self, evals: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        assert_eq!(evals.len() % 2, 0, "n must be even");
+        let (even, odd) = even_odd_split(evals.to_vec());
+        interleave(
+            self.inverse_negacyclic(&even),
+            self.inverse_negacyclic(&odd),
+        )
+    }
+
+    /// Multiplies the outputs of `forward_incomplete`, which are pairs `(a_0, a_1)` standing for `a_0 + a_1 X`
+    /// modulo `X^2 - g_j`:
+    ///
+    /// `(a_0 + a_1 X)(b_0 + b_1 X) = (a_0 b_0 + a_1 b_1 g_j) + (a_0 b_1 + a_1 b_0) X`
+    pub fn mul_incomplete(
+        &self,
+        a: &[FieldElement<F>],
+        b: &[FieldElement<F>],
+    ) -> Vec<FieldElement<F>> {
+        assert_eq!(a.len(), b.len(), "size mismatch");
+        let n = a.len();
+        assert!(self.size() >= n, "too many inputs");
+
+        // psi = w^stride is a primitive n-th root, and g_j = psi^(2j + 1)
+        let stride = self.size() / n;
+        a.chunks(2)
+            .zip(b.chunks(2))
+            .enumerate()
+            .flat_map(|(j, (a, b))| {
+                let g_j = self.twiddle((2 * j + 1) * stride);
+                [
+                    &a[0] * &b[0] + &a[1] * &b[1] * g_j,
+                    &a[0] * &b[1] + &a[1] * &b[0],
+                ]
+            })
+            .collect()
+    }
+
+    /// Returns a primitive `2n`-th root of unity, for the negacyclic transforms of size `n`.
+    fn negacyclic_root(&self, n: usize) -> FieldElement<F> {
+        assert!(self.size() >= 2 * n, "NTT must have a size of at least 2n");
+        self.twiddle(self.size() / (2 * n))
+    }
+
+    /// Same as `forward_in_place` for many `columns` of the same size at once, e.g. the columns of a trace.
+    ///
+    /// The butterflies are applied level by level to all columns, so each twiddle factor is fetched once per
+    /// level and chunk, and shared by every column.
+    pub fn forward_batch(&self, columns: &mut [Vec<FieldElement<F>>]) {
+        let Some(n) = columns.first().map(Vec::len) else {
+            return;
+        };
+        assert!(
+            columns.iter().all(|column| column.len() == n),
+            "columns must have the same size"
+        );
+        assert!(self.size() >= n, "too many inputs");
+
+        // same as `dit`, with the loop over the columns within the one over the chunks
+        let mut len = n;
+        while len >= 2 {
+            let half = len >> 1;
+            for (k, w) in self.twiddles.iter().take(n / len).enumerate() {
+                for column in columns.iter_mut() {
+                    let (lo, hi) = column[k * len..(k + 1) * len].split_at_mut(half);
+                    for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+                        let t = w * &*b;
+                        *b = &*a - &t;
+                        *a = &*a + t;
+                    }
+                }
+            }
+            len >>= 1;
+        }
+
+        for column in columns.iter_mut() {
+            bit_reverse_permute(column);
+        }
+    }
+
+    /// Radix-2 Cooley-Tukey transform of `values`. For `n` values, we need a primitive `n`-th root, which is
+    /// `w^stride` with `stride = size / n`.
+    fn transform(&self, values: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        assert!(self.size() >= values.len(), "too many inputs");
+        let n = values.len();
         if n == 1 {
-            return coeffs.to_vec();
+            return values.to_vec();
         }
         assert_eq!(n.count_ones(), 1, "n must be a power of 2");
 
         let half = n >> 1;
-        let (even, odd) = even_odd_split(coeffs.to_vec());
-        let (even, odd) = (self.forward(&even), self.forward(&odd));
+        let stride = self.size() / n;
+        let (even, odd) = even_odd_split(values.to_vec());
+        let (even, odd) = (self.transform(&even), self.transform(&odd));
 
         let mut res = vec![FieldElement::zero(); n];
         for j in 0..half {
+            // w_n^j = w^(j * stride)
+            let w_j = self.twiddle(j * stride);
+
             // A_j = E_j + w^j * O_j
-            res[j] = even[j].clone() + self.twiddles[j].clone() * odd[j].clone();
+            res[j] = even[j].clone() + w_j.clone() * odd[j].clone();
 
             // A_{j + n/2} = E_j - w^j * O_j
-            res[j + half] = even[j].clone() - self.twiddles[j].clone() * odd[j].clone();
+            res[j + half] = even[j].clone() - w_j * odd[j].clone();
         }
 
         res
     }
 }
 
+impl<F: IsFFTField<BaseType: Send + Sync> + 'static> NTT<F> {
+    /// Returns the NTT of size `2^log_n` with the primitive root of unity of the field, from `IsFFTField`, or
+    /// `None` if the size exceeds the two-adicity of the field.
+    ///
+    /// The twiddle tables are computed once for each field and size, and cached for the later calls.
+    pub fn for_field(log_n: u64) -> Option<Arc<Self>> {
+        type Cache = HashMap<(TypeId, u64), Arc<dyn Any + Send + Sync>>;
+        static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+        // the lock is not held while the tables are computed, and entries are only inserted once complete, so a
+        // poisoned lock still holds a valid cache
+        let cache = || {
+            CACHE
+                .get_or_init(Default::default)
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        };
+        let key = (TypeId::of::<F>(), log_n);
+        let cached = cache().get(&key).cloned();
+        let ntt = match cached {
+            Some(ntt) => ntt,
+            None => {
+                if log_n > F::TWO_ADICITY || log_n >= u64::BITS as u64 {
+                    return None;
+                }
+                log::debug!("computing twiddles for NTT of size 2^{}", log_n);
+                let w = F::get_primitive_root_of_unity(log_n).ok()?;
+                let ntt: Arc<dyn Any + Send + Sync> = Arc::new(Self::new(w, 1 << log_n));
+                // another thread may have computed them meanwhile, in which case we return theirs
+                cache().entry(key).or_insert(ntt).clone()
+            }
+        };
+
+        Some(
+            ntt.downcast()
+                .expect("cached NTT is over the field of its key"),
+        )
+    }
+}
+
+/// Reorders the elements of `values` by bit-reversing their indices, e.g. for `n = 8` the element at index
+/// `3 = 0b011` is swapped with the one at `6 = 0b110`. Applying it twice gives back the original order.
+pub fn bit_reverse_permute<T>(values: &mut [T]) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    assert_eq!(n.count_ones(), 1, "n must be a power of 2");
+
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+/// Returns `i` with its `log(n)` bits reversed, for a power of two `n`.
+fn reverse_bits(i: usize, n: usize) -> usize {
+    match n.trailing_zeros() {
+        0 => 0,
+        log_n => i.reverse_bits() >> (usize::BITS - log_n),
+    }
+}
+
+/// Returns `w^0, w^1, ..., w^(n-1)` in bit-reversed order.
+fn bit_reversed_powers<F: IsField>(w: &FieldElement<F>, n: usize) -> Vec<FieldElement<F>> {
+    let mut powers = std::iter::successors(Some(FieldElement::one()), |w_i| Some(w_i * w))
+        .take(n)
+        .collect::<Vec<_>>();
+    bit_reverse_permute(&mut powers);
+    powers
+}
+
+/// Iterative Cooley-Tukey transform (decimation-in-time) of `values` in-place, with the `twiddles` of a
+/// transform of size at least `n` in bit-reversed order; the output is in bit-reversed order.
+///
+/// The chunks of size `len = n, n/2, ..., 2` combine their halves with the butterfly `(a + w b, a - w b)`, where
+/// `w = twiddles[k]` is the same for the whole `k`-th chunk, which is why the twiddles are kept in bit-reversed
+/// order.
+fn dit<F: IsField>(values: &mut [FieldElement<F>], twiddles: &[FieldElement<F>]) {
+    let n = values.len();
+    assert!(twiddles.len() * 2 >= n, "too many inputs");
+
+    let mut len = n;
+    while len >= 2 {
+        let half = len >> 1;
+        for (chunk, w) in values.chunks_mut(len).zip(twiddles) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = w * &*b;
+                *b = &*a - &t;
+                *a = &*a + t;
+            }
+        }
+        len >>= 1;
+    }
+}
+
+/// Iterative Gentleman-Sande transform (decimation-in-frequency) of `values` in-place, with the `twiddles` of a
+/// transform of size at least `n` in bit-reversed order; the input is in bit-reversed order.
+///
+/// This is `dit` transposed, which is the same transform as the NTT matrix is symmetric: the chunks of size
+/// `len = 2, 4, ..., n` are combined with the butterfly `(a + b, (a - b) w)` where `w = twiddles[k]` for the
+/// `k`-th chunk.
+fn dif<F: IsField>(values: &mut [FieldElement<F>], twiddles: &[FieldElement<F>]) {
+    let n = values.len();
+    assert!(twiddles.len() * 2 >= n, "too many inputs");
+
+    let mut len = 2;
+    while len <= n {
+        let half = len >> 1;
+        for (chunk, w) in values.chunks_mut(len).zip(twiddles) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = &*a - &*b;
+                *a = &*a + &*b;
+                *b = t * w;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Interleaves `even` and `odd` into a single vector, i.e. the inverse of `even_odd_split`.
+fn interleave<T>(even: Vec<T>, odd: Vec<T>) -> Vec<T> {
+    even.into_iter()
+        .zip(odd)
+        .flat_map(|(e, o)| [e, o])
+        .collect()
+}
+
+/// Multiplies `values[i]` by `h^i` in-place.
+fn scale_by_powers<F: IsField>(values: &mut [FieldElement<F>], h: &FieldElement<F>) {
+    let mut h_i = FieldElement::<F>::one();
+    for value in values.iter_mut() {
+        *value = &*value * &h_i;
+        h_i *= h;
+    }
+}
+
 /// Splits a given array into two arrays, one containing the elements at even indices and the other
 /// containing the elements at odd indices.
 pub fn even_odd_split<T>(arr: Vec<T>) -> (Vec<T>, Vec<T>) {
@@ -58,6 +426,16 @@ pub fn even_odd_split<T>(arr: Vec<T>) -> (Vec<T>, Vec<T>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lambdaworks_math::{
+        field::{
+            fields::{
+                fft_friendly::babybear::Babybear31PrimeField,
+                u64_goldilocks_field::Goldilocks64Field, u64_prime_field::U64PrimeField,
+            },
+            traits::IsPrimeField,
+        },
+        polynomial::Polynomial,
+    };
 
     #[test]
     fn test_even_odd_split() {
@@ -67,4 +445,263 @@ mod tests {
         assert_eq!(even, vec![1, 3, 5, 7]);
         assert_eq!(odd, vec![2, 4, 6, 8]);
     }
+
+    #[test]
+    fn test_bit_reverse_permute() {
+        let mut arr = (0..8).collect::<Vec<_>>();
+        bit_reverse_permute(&mut arr);
+        assert_eq!(arr, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+
+        bit_reverse_permute(&mut arr);
+        assert_eq!(arr, (0..8).collect::<Vec<_>>());
+
+        let mut arr = vec![42];
+        bit_reverse_permute(&mut arr);
+        assert_eq!(arr, vec![42]);
+    }
+
+    /// Creates an NTT with `2^log_n` twiddles, given a generator `g` of the multiplicative group of the field,
+    /// and checks `forward` against polynomial evaluation and `inverse(forward(x)) == x` for every power-of-two
+    /// size up to the number of twiddles, with random coefficients; the recursive, DIT and DIF ones must agree.
+    fn test_round_trip<F: IsPrimeField<RepresentativeType = u64>>(g: u64, log_n: u32) {
+        let p_minus_one = (-FieldElement::<F>::one()).representative();
+        let n = 1u64 << log_n;
+        assert_eq!(p_minus_one % n, 0, "n must divide p - 1");
+        let ntt = NTT::new(FieldElement::<F>::from(g).pow(p_minus_one / n), n);
+
+        for log_size in 0..=log_n {
+            let size = 1usize << log_size;
+            let coeffs = (0..size)
+                .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+                .collect::<Vec<_>>();
+
+            let evals = ntt.forward(&coeffs);
+            let poly = Polynomial::new(&coeffs);
+            let stride = n as usize / size;
+            // naive evaluation is quadratic, so we check some of the points for larger sizes
+            for (j, eval) in evals.iter().enumerate().step_by((size / 16).max(1)) {
+                assert_eq!(*eval, poly.evaluate(&ntt.twiddle(j * stride)));
+            }
+
+            assert_eq!(ntt.inverse(&evals), coeffs);
+            assert_eq!(ntt.forward(&ntt.inverse(&coeffs)), coeffs);
+
+            // all algorithms agree
+            assert_eq!(ntt.forward_recursive(&coeffs), evals);
+            let mut values = coeffs.clone();
+            ntt.forward_in_place_dif(&mut values);
+            assert_eq!(values, evals);
+            ntt.inverse_in_place(&mut values);
+            assert_eq!(values, coeffs);
+        }
+    }
+
+    #[test]
+    fn test_for_field() {
+        type F = Babybear31PrimeField;
+        let ntt = NTT::<F>::for_field(10).unwrap();
+        assert!(Arc::ptr_eq(&ntt, &NTT::<F>::for_field(10).unwrap()));
+        assert!(!Arc::ptr_eq(&ntt, &NTT::<F>::for_field(9).unwrap()));
+        assert_eq!(ntt.size(), 1 << 10);
+
+        // half-size tables in bit-reversed order
+        let w = F::get_primitive_root_of_unity(10).unwrap();
+        assert_eq!(ntt.twiddles.len(), 1 << 9);
+        for (i, w_i) in ntt.twiddles.iter().enumerate() {
+            let j = i.reverse_bits() >> (usize::BITS - 9);
+            assert_eq!(*w_i, w.pow(j));
+            assert_eq!(ntt.twiddle(j), *w_i);
+            assert_eq!(ntt.twiddle(j + (1 << 9)), -w_i);
+            assert_eq!(&ntt.inv_twiddles[i] * w_i, FieldElement::one());
+        }
+
+        let coeffs = (0..1 << 10)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+        assert_eq!(ntt.forward(&coeffs), NTT::new(w, 1 << 10).forward(&coeffs));
+    }
+
+    #[test]
+    fn test_for_field_too_large() {
+        // sizes beyond the two-adicity of the field, or that overflow, do not affect the later calls
+        type F = Babybear31PrimeField;
+        assert!(NTT::<F>::for_field(F::TWO_ADICITY + 1).is_none());
+        assert!(NTT::<F>::for_field(64).is_none());
+        assert_eq!(NTT::<F>::for_field(4).unwrap().size(), 1 << 4);
+    }
+
+    #[test]
+    fn test_coset() {
+        // 3 generates the multiplicative group, with 65537 - 1 = 2^16, so it is not in the subgroup of size 2^8
+        type F = U64PrimeField<65537>;
+        let n = 1u64 << 8;
+        let w = FieldElement::<F>::from(3).pow(65536 / n);
+        let ntt = NTT::new(w, n);
+        let offset = FieldElement::<F>::from(3);
+
+        let coeffs = (0..n)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+        let evals = ntt.forward_coset(&coeffs, &offset);
+        let poly = Polynomial::new(&coeffs);
+        for (j, eval) in evals.iter().enumerate() {
+            assert_eq!(*eval, poly.evaluate(&(offset * ntt.twiddle(j))));
+        }
+        assert_eq!(ntt.inverse_coset(&evals, &offset), coeffs);
+
+        // offset 1 is the subgroup itself
+        assert_eq!(
+            ntt.forward_coset(&coeffs, &FieldElement::one()),
+            ntt.forward(&coeffs)
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        // 7 generates the multiplicative group, with p - 1 = 2^32 . (2^32 - 1)
+        type F = Goldilocks64Field;
+        let n = 1u64 << 6;
+        let w = FieldElement::<F>::from(7).pow((u64::MAX - u64::from(u32::MAX)) / n);
+        let ntt = NTT::new(w, n);
+
+        let mut columns = (0..5)
+            .map(|_| {
+                (0..n)
+                    .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let polys = columns
+            .iter()
+            .map(|column| Polynomial::new(column))
+            .collect::<Vec<_>>();
+        let expected = columns
+            .iter()
+            .map(|column| ntt.forward(column))
+            .collect::<Vec<_>>();
+
+        ntt.forward_batch(&mut columns);
+        assert_eq!(columns, expected);
+        for (column, poly) in columns.iter().zip(&polys) {
+            for (j, eval) in column.iter().enumerate() {
+                assert_eq!(*eval, poly.evaluate(&ntt.twiddle(j)));
+            }
+        }
+
+        // no columns is a no-op
+        ntt.forward_batch(&mut []);
+    }
+
+    /// Multiplies `a` and `b` modulo `X^n + 1` by the schoolbook method, where `X^(n + k) = -X^k`.
+    fn schoolbook_negacyclic<F: IsField>(
+        a: &[FieldElement<F>],
+        b: &[FieldElement<F>],
+    ) -> Vec<FieldElement<F>> {
+        let n = a.len();
+        let mut res = vec![FieldElement::<F>::zero(); n];
+        for (i, a_i) in a.iter().enumerate() {
+            for (j, b_j) in b.iter().enumerate() {
+                let a_i_b_j = a_i * b_j;
+                if i + j < n {
+                    res[i + j] = &res[i + j] + a_i_b_j;
+                } else {
+                    res[i + j - n] = &res[i + j - n] - a_i_b_j;
+                }
+            }
+        }
+        res
+    }
+
+    fn random_ring_element<F: IsField>(n: usize) -> Vec<FieldElement<F>> {
+        (0..n)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect()
+    }
+
+    #[test]
+    fn test_negacyclic_dilithium() {
+        // 1753 is a primitive 512-th root of unity modulo q = 8380417, for the ring Z_q[X]/(X^256 + 1)
+        type F = U64PrimeField<8380417>;
+        let ntt = NTT::new(FieldElement::<F>::from(1753), 512);
+        let n = 256;
+
+        let (a, b) = (random_ring_element::<F>(n), random_ring_element::<F>(n));
+        let (a_hat, b_hat) = (ntt.forward_negacyclic(&a), ntt.forward_negacyclic(&b));
+        assert_eq!(ntt.inverse_negacyclic(&a_hat), a);
+
+        // evaluations at the roots of X^n + 1
+        let psi = FieldElement::<F>::from(1753);
+        let poly = Polynomial::new(&a);
+        for (j, eval) in a_hat.iter().enumerate().step_by(16) {
+            assert_eq!(*eval, poly.evaluate(&psi.pow(2 * j as u64 + 1)));
+        }
+
+        let c_hat = a_hat
+            .iter()
+            .zip(&b_hat)
+            .map(|(a, b)| a * b)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ntt.inverse_negacyclic(&c_hat),
+            schoolbook_negacyclic(&a, &b)
+        );
+
+        // the incomplete one works as well
+        let c_hat = ntt.mul_incomplete(&ntt.forward_incomplete(&a), &ntt.forward_incomplete(&b));
+        assert_eq!(
+            ntt.inverse_incomplete(&c_hat),
+            schoolbook_negacyclic(&a, &b)
+        );
+    }
+
+    #[test]
+    fn test_incomplete_kyber() {
+        // 17 is a primitive 256-th root of unity modulo q = 3329, but there is no 512-th root as q - 1 = 2^8 . 13
+        type F = U64PrimeField<3329>;
+        let ntt = NTT::new(FieldElement::<F>::from(17), 256);
+        let n = 256;
+
+        for _ in 0..4 {
+            let (a, b) = (random_ring_element::<F>(n), random_ring_element::<F>(n));
+            let (a_hat, b_hat) = (ntt.forward_incomplete(&a), ntt.forward_incomplete(&b));
+            assert_eq!(ntt.inverse_incomplete(&a_hat), a);
+
+            let c_hat = ntt.mul_incomplete(&a_hat, &b_hat);
+            assert_eq!(
+                ntt.inverse_incomplete(&c_hat),
+                schoolbook_negacyclic(&a, &b)
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverse_17() {
+        // 3 generates the multiplicative group, with 17 - 1 = 2^4
+        test_round_trip::<U64PrimeField<17>>(3, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "w must be primitive")]
+    fn test_non_primitive_root() {
+        // 13 = 3^4 has order 4, so it is an 8-th root of unity but not a primitive one, even though 13^7 != 1
+        NTT::new(FieldElement::<U64PrimeField<17>>::from(13), 8);
+    }
+
+    #[test]
+    fn test_inverse_65537() {
+        // 3 generates the multiplicative group, with 65537 - 1 = 2^16
+        test_round_trip::<U64PrimeField<65537>>(3, 16);
+    }
+
+    #[test]
+    fn test_inverse_goldilocks() {
+        // 7 generates the multiplicative group, with p - 1 = 2^32 . (2^32 - 1)
+        test_round_trip::<Goldilocks64Field>(7, 12);
+    }
+
+    #[test]
+    fn test_inverse_stark101() {
+        // 5 generates the multiplicative group, with p - 1 = 3 . 2^30
+        test_round_trip::<U64PrimeField<3221225473>>(5, 12);
+    }
 }
@@ -0,0 +1,201 @@
+use lambdaworks_math::{
+    field::{element::FieldElement, traits::IsFFTField},
+    polynomial::Polynomial,
+};
+
+use crate::NTT;
+
+/// Returns an NTT of size `n`, which must be a power of two within the two-adicity of the field.
+fn ntt_of_size<F: IsFFTField>(n: usize) -> NTT<F> {
+    let log_n = n.trailing_zeros() as u64;
+    let w =
+        F::get_primitive_root_of_unity(log_n).expect("size exceeds the two-adicity of the field");
+    NTT::new(w, n as u64)
+}
+
+/// Multiplies two polynomials via NTT.
+///
+/// Both polynomials are padded with zeros to the next power of two `n` that can hold the product, i.e. at least
+/// `deg(a) + deg(b) + 1` coefficients, so that the evaluations at the `n`-th roots of unity determine the product.
+/// They are transformed, multiplied pointwise and transformed back.
+pub fn poly_mul<F: IsFFTField>(
+    a: &Polynomial<FieldElement<F>>,
+    b: &Polynomial<FieldElement<F>>,
+) -> Polynomial<FieldElement<F>> {
+    if a.coeff_len() == 0 || b.coeff_len() == 0 {
+        return Polynomial::zero();
+    }
+
+    // NTT of size 1 has no primitive root other than 1, which `NTT::new` does not accept
+    let n = (a.coeff_len() + b.coeff_len() - 1)
+        .next_power_of_two()
+        .max(2);
+    let ntt = ntt_of_size::<F>(n);
+
+    let mut a_evals = a.coefficients().to_vec();
+    a_evals.resize(n, FieldElement::zero());
+    ntt.forward_in_place(&mut a_evals);
+
+    let mut b_evals = b.coefficients().to_vec();
+    b_evals.resize(n, FieldElement::zero());
+    ntt.forward_in_place(&mut b_evals);
+
+    for (a_j, b_j) in a_evals.iter_mut().zip(&b_evals) {
+        *a_j = &*a_j * b_j;
+    }
+    ntt.inverse_in_place(&mut a_evals);
+
+    Polynomial::new(&a_evals)
+}
+
+/// Returns the inverse of `f` as a power series modulo `x^k`, i.e. `g` such that `f . g = 1 (mod x^k)`, which
+/// exists when the constant term of `f` is not zero.
+///
+/// This is Newton iteration: starting from `g = 1 / f(0)` that is correct modulo `x`, each step computes
+/// `g' = g . (2 - f . g)`, which doubles the number of correct coefficients.
+pub fn poly_inverse_mod<F: IsFFTField>(
+    f: &Polynomial<FieldElement<F>>,
+    k: usize,
+) -> Polynomial<FieldElement<F>> {
+    let f_0 = f.coefficients().first().expect("polynomial is zero");
+    let mut g = Polynomial::new(&[f_0.inv().expect("constant term must not be zero")]);
+
+    let two = Polynomial::new(&[FieldElement::<F>::from(2u64)]);
+    let mut precision = 1;
+    while precision < k {
+        precision = (precision << 1).min(k);
+        let f_g = truncate(&poly_mul(&truncate(f, precision), &g), precision);
+        g = truncate(&poly_mul(&g, &(two.clone() - f_g)), precision);
+    }
+
+    truncate(&g, k)
+}
+
+/// Divides `a` by `b`, returning the quotient `q` and the remainder `r` such that `a = q . b + r` and
+/// `deg(r) < deg(b)`.
+///
+/// With `n = deg(a)` and `m = deg(b)`, the reversed polynomials `rev(p)(x) = x^deg(p) . p(1/x)` satisfy
+/// `rev(a) = rev(q) . rev(b) (mod x^(n - m + 1))`, where `rev(q)` has `n - m + 1` coefficients. The constant term
+/// of `rev(b)` is the leading coefficient of `b`, so it has an inverse modulo `x^(n - m + 1)`, and we get the
+/// quotient from `rev(q) = rev(a) . rev(b)^(-1)` with a couple of multiplications; the remainder is `a - q . b`.
+///
+/// ## Panics
+///
+/// If `b` is zero.
+pub fn poly_div_rem<F: IsFFTField>(
+    a: &Polynomial<FieldElement<F>>,
+    b: &Polynomial<FieldElement<F>>,
+) -> (Polynomial<FieldElement<F>>, Polynomial<FieldElement<F>>) {
+    assert!(b.coeff_len() > 0, "division by zero polynomial");
+    if a.coeff_len() < b.coeff_len() {
+        return (Polynomial::zero(), a.clone());
+    }
+
+    let (n, m) = (a.coeff_len() - 1, b.coeff_len() - 1);
+    let k = n - m + 1;
+    let rev_b_inv = poly_inverse_mod(&reverse(b, m + 1), k);
+    let rev_q = truncate(&poly_mul(&truncate(&reverse(a, n + 1), k), &rev_b_inv), k);
+    let q = reverse(&rev_q, k);
+
+    let r = a.clone() - poly_mul(&q, b);
+    (q, r)
+}
+
+/// Returns `p mod x^k`, i.e. its first `k` coefficients.
+fn truncate<F: IsFFTField>(
+    p: &Polynomial<FieldElement<F>>,
+    k: usize,
+) -> Polynomial<FieldElement<F>> {
+    Polynomial::new(&p.coefficients()[..k.min(p.coeff_len())])
+}
+
+/// Returns `x^(len - 1) . p(1/x)`, i.e. the first `len` coefficients of `p` in reverse order.
+fn reverse<F: IsFFTField>(
+    p: &Polynomial<FieldElement<F>>,
+    len: usize,
+) -> Polynomial<FieldElement<F>> {
+    let mut coeffs = p.coefficients().to_vec();
+    coeffs.resize(len, FieldElement::zero());
+    coeffs.reverse();
+    Polynomial::new(&coeffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lambdaworks_math::field::fields::{
+        fft_friendly::{babybear::Babybear31PrimeField, stark_252_prime_field::Stark252PrimeField},
+        u64_prime_field::U64PrimeField,
+    };
+
+    fn random_polynomial<F: IsFFTField>(num_coeffs: usize) -> Polynomial<FieldElement<F>> {
+        Polynomial::new(
+            &(0..num_coeffs)
+                .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Checks `poly_mul` against the naive `*`, and `poly_div_rem` against `long_division_with_remainder` and `/`.
+    fn test_mul_div<F: IsFFTField>(max_len: usize) {
+        for len_a in 0..max_len {
+            for len_b in 1..max_len {
+                let a = random_polynomial::<F>(len_a);
+                let b = random_polynomial::<F>(len_b);
+                if b.coeff_len() == 0 {
+                    // random coefficients may all be zero over a tiny field
+                    continue;
+                }
+
+                assert_eq!(poly_mul(&a, &b), a.clone() * b.clone());
+
+                let (q, r) = poly_div_rem(&a, &b);
+                let (expected_q, expected_r) = a.clone().long_division_with_remainder(&b);
+                assert_eq!(q, expected_q);
+                assert_eq!(r, expected_r);
+                assert_eq!(q, a.clone() / b.clone());
+                assert_eq!(poly_mul(&q, &b) + r, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_f17() {
+        // two-adicity is 4, so the product must fit in 16 coefficients
+        test_mul_div::<U64PrimeField<17>>(8);
+    }
+
+    #[test]
+    fn test_babybear() {
+        test_mul_div::<Babybear31PrimeField>(20);
+    }
+
+    #[test]
+    fn test_stark252() {
+        test_mul_div::<Stark252PrimeField>(12);
+    }
+
+    #[test]
+    fn test_large() {
+        type F = Babybear31PrimeField;
+        let a = random_polynomial::<F>(1000);
+        let b = random_polynomial::<F>(300);
+        assert_eq!(poly_mul(&a, &b), a.clone() * b.clone());
+
+        let (q, r) = poly_div_rem(&a, &b);
+        assert_eq!(q, a.clone() / b.clone());
+        assert!(r.coeff_len() < b.coeff_len());
+        assert_eq!(poly_mul(&q, &b) + r, a);
+    }
+
+    #[test]
+    fn test_inverse_mod() {
+        type F = Babybear31PrimeField;
+        let f = random_polynomial::<F>(10);
+        for k in 1..40 {
+            let g = poly_inverse_mod(&f, k);
+            let one = Polynomial::new(&[FieldElement::<F>::one()]);
+            assert_eq!(truncate(&poly_mul(&f, &g), k), one);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+use rsa::RSA;
+
+/// Returns the Mersenne prime `2^k - 1`.
+fn mersenne(k: u32) -> BigUint {
+    (BigUint::from(1u32) << k) - 1u32
+}
+
+/// Benchmarks encryption and decryption with moduli of about 92, 196, 648 and 1128 bits, made of Mersenne primes
+/// so that no prime generation is needed; `e = 65537` is coprime to `phi(n)` for all of them.
+fn bench_rsa(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rsa");
+
+    for (k_p, k_q) in [(31, 61), (89, 107), (127, 521), (521, 607)] {
+        let rsa = RSA::new(mersenne(k_p), mersenne(k_q));
+        let (_, n) = rsa.public_key();
+        let m = n - 2u32;
+        let c = rsa.encrypt(m.clone());
+
+        let bits = n.bits();
+        group.bench_with_input(BenchmarkId::new("encrypt", bits), &m, |b, m| {
+            b.iter(|| rsa.encrypt(m.clone()))
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", bits), &c, |b, c| {
+            b.iter(|| rsa.decrypt(c.clone()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_rsa);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::{
+        fft_friendly::stark_252_prime_field::Stark252PrimeField,
+        u64_goldilocks_field::Goldilocks64Field,
+    },
+    traits::IsField,
+};
+use shamir_secret_share::ShamirSecretShare;
+
+/// Benchmarks creating `n` shares with threshold `k`, and reconstructing the secret from `k` of them.
+fn bench_field<F: IsField>(c: &mut Criterion, field: &str) {
+    let mut group = c.benchmark_group(format!("shamir/{}", field));
+    group.sample_size(10);
+
+    for (n, k) in [(8, 4), (32, 16), (128, 64), (512, 256)] {
+        let shamir = ShamirSecretShare::<F>::new(n, k);
+        let secret = FieldElement::<F>::from(rand::random::<u64>());
+        let shares = shamir.create_shares(secret.clone());
+
+        let id = format!("{}/{}", k, n);
+        group.bench_with_input(
+            BenchmarkId::new("create_shares", &id),
+            &secret,
+            |b, secret| b.iter(|| shamir.create_shares(secret.clone())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("reconstruct_secret", &id),
+            &shares,
+            |b, shares| b.iter(|| shamir.reconstruct_secret(shares[..k].to_vec())),
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_shamir(c: &mut Criterion) {
+    bench_field::<Goldilocks64Field>(c, "goldilocks");
+    bench_field::<Stark252PrimeField>(c, "stark252");
+}
+
+criterion_group!(benches, bench_shamir);
+criterion_main!(benches);
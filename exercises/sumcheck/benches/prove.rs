@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lambdaworks_math::{
+    field::{element::FieldElement, fields::u64_prime_field::U64PrimeField, traits::IsField},
+    polynomial::dense_multilinear_poly::DenseMultilinearPolynomial,
+    traits::ByteConversion,
+};
+use sumcheck::{sumcheck::SumCheck, utils::random_evals};
+
+/// Benchmarks `SumCheck::prove` over the given field for 4 to 12 variables, along with the evaluation of the
+/// multilinear polynomial by LambdaWorks at a random point, which the prover does for every term of each round.
+fn bench_field<F: IsField>(c: &mut Criterion, field: &str)
+where
+    <F as IsField>::BaseType: Send + Sync,
+    FieldElement<F>: ByteConversion,
+{
+    let mut group = c.benchmark_group(format!("sumcheck/{}", field));
+    group.sample_size(10);
+
+    for num_vars in (4..=12).step_by(2) {
+        let poly = DenseMultilinearPolynomial::new(random_evals::<F>(num_vars));
+        let sumcheck = SumCheck::new(poly.clone());
+        let r = (0..num_vars)
+            .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+
+        group.bench_with_input(
+            BenchmarkId::new("prove", num_vars),
+            &sumcheck,
+            |b, sumcheck| b.iter(|| sumcheck.prove()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("lambdaworks/evaluate", num_vars),
+            &poly,
+            |b, poly| b.iter(|| poly.evaluate(r.clone()).unwrap()),
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_sumcheck(c: &mut Criterion) {
+    bench_field::<U64PrimeField<65537>>(c, "65537");
+    bench_field::<U64PrimeField<18446744069414584321>>(c, "goldilocks");
+}
+
+criterion_group!(benches, bench_sumcheck);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lambdaworks_math::{field::element::FieldElement, polynomial::Polynomial};
+use stark101::{
+    babybear::Babybear31PrimeField,
+    fft::{evaluate_offset_fft, StarkField, TwoAdicField},
+    field::Stark101PrimeField,
+    goldilocks::Goldilocks64Field,
+    options::StarkOptions,
+    program::{fibonacci_square, FibonacciSqAir},
+    prover::Stark101Prover,
+};
+
+/// Proves FibonacciSq with `2^k - 1` steps over the given field, so that the trace domain has `2^k` elements.
+fn bench_prove<F: StarkField>(c: &mut Criterion, field: &str) {
+    let mut group = c.benchmark_group(format!("prove/{}", field));
+    group.sample_size(10);
+
+    let options = StarkOptions::default();
+    for log_n in 10..=18 {
+        let n = (1 << log_n) - 1;
+        let a_0 = FieldElement::<F>::one();
+        let trace = vec![fibonacci_square(
+            a_0.clone(),
+            FieldElement::from(3141592u64),
+            n,
+        )];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1].clone(),
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("fibonacci_square", 1 << log_n),
+            &trace,
+            |b, trace| b.iter(|| Stark101Prover::prove(&air, trace, &options)),
+        );
+    }
+
+    group.finish();
+}
+
+/// Compares the low-degree extension of the prover against the FFT of LambdaWorks over BabyBear, which both
+/// support, for polynomials of `2^k` coefficients over a coset 8 times larger.
+fn bench_lde(c: &mut Criterion) {
+    type F = Babybear31PrimeField;
+    let mut group = c.benchmark_group("lde/babybear");
+    group.sample_size(10);
+
+    let blowup_factor = 8;
+    let offset = FieldElement::<F>::from(F::GENERATOR);
+    for log_n in (10..=18).step_by(2) {
+        let n = 1usize << log_n;
+        let domain_size = n * blowup_factor;
+        let root = F::primitive_root_of_unity(domain_size.trailing_zeros() as u64);
+        let poly = Polynomial::new(
+            &(0..n)
+                .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+                .collect::<Vec<_>>(),
+        );
+
+        group.throughput(Throughput::Elements(domain_size as u64));
+        group.bench_with_input(BenchmarkId::new("stark101", n), &poly, |b, poly| {
+            b.iter(|| evaluate_offset_fft(poly, domain_size, &root, &offset))
+        });
+        group.bench_with_input(BenchmarkId::new("lambdaworks", n), &poly, |b, poly| {
+            b.iter(|| {
+                Polynomial::evaluate_offset_fft::<F>(poly, blowup_factor, None, &offset).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_prover(c: &mut Criterion) {
+    bench_prove::<Stark101PrimeField>(c, "stark101");
+    bench_prove::<Goldilocks64Field>(c, "goldilocks");
+    bench_prove::<Babybear31PrimeField>(c, "babybear");
+    bench_lde(c);
+}
+
+criterion_group!(benches, bench_prover);
+criterion_main!(benches);
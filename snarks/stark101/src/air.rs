@@ -0,0 +1,156 @@
+use std::ops::{Add, Mul, Sub};
+
+use lambdaworks_math::field::{element::FieldElement, traits::IsField};
+
+/// A polynomial expression over the trace columns at consecutive rows, i.e. over `f_j(x)`, `f_j(g . x)`,
+/// `f_j(g^2 . x)` and so on for each column `j`.
+///
+/// Expressions can be built with the usual `+`, `-` and `*` operators, for example the FibonacciSq transition
+/// `f(g^2 . x) - f(g . x)^2 - f(x)^2` over the first column is written as:
+///
+/// ```text
+/// Trace(0, 2) - Trace(0, 1) * Trace(0, 1) - Trace(0, 0) * Trace(0, 0)
+/// ```
+#[derive(Debug, Clone)]
+pub enum AirExpr<F: IsField> {
+    /// A constant value.
+    Constant(FieldElement<F>),
+    /// The trace at the given column and row offset, e.g. `Trace(1, 2)` stands for `f_1(g^2 . x)`.
+    Trace(usize, usize),
+    Add(Box<AirExpr<F>>, Box<AirExpr<F>>),
+    Sub(Box<AirExpr<F>>, Box<AirExpr<F>>),
+    Mul(Box<AirExpr<F>>, Box<AirExpr<F>>),
+}
+
+impl<F: IsField> AirExpr<F> {
+    /// Evaluates the expression given the trace values within `frame`, such that `frame[offset][column]`
+    /// is the value of the column at that row offset, and a function `constant` that maps constants to the same type.
+    ///
+    /// This is generic so that the prover can evaluate it pointwise over the trace values in the base field, and
+    /// the verifier can evaluate it over the out-of-domain values in the extension.
+    pub fn evaluate<T>(&self, frame: &[Vec<T>], constant: &impl Fn(&FieldElement<F>) -> T) -> T
+    where
+        T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    {
+        match self {
+            AirExpr::Constant(c) => constant(c),
+            AirExpr::Trace(column, offset) => frame[*offset][*column].clone(),
+            AirExpr::Add(a, b) => a.evaluate(frame, constant) + b.evaluate(frame, constant),
+            AirExpr::Sub(a, b) => a.evaluate(frame, constant) - b.evaluate(frame, constant),
+            AirExpr::Mul(a, b) => a.evaluate(frame, constant) * b.evaluate(frame, constant),
+        }
+    }
+
+    /// Degree of the expression with respect to the trace, e.g. `f(x)^2` has degree 2.
+    pub fn degree(&self) -> usize {
+        match self {
+            AirExpr::Constant(_) => 0,
+            AirExpr::Trace(_, _) => 1,
+            AirExpr::Add(a, b) | AirExpr::Sub(a, b) => a.degree().max(b.degree()),
+            AirExpr::Mul(a, b) => a.degree() + b.degree(),
+        }
+    }
+
+    /// Largest row offset referred to within the expression.
+    pub fn max_offset(&self) -> usize {
+        match self {
+            AirExpr::Constant(_) => 0,
+            AirExpr::Trace(_, offset) => *offset,
+            AirExpr::Add(a, b) | AirExpr::Sub(a, b) | AirExpr::Mul(a, b) => {
+                a.max_offset().max(b.max_offset())
+            }
+        }
+    }
+}
+
+impl<F: IsField> Add for AirExpr<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        AirExpr::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: IsField> Sub for AirExpr<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        AirExpr::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: IsField> Mul for AirExpr<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        AirExpr::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+/// A boundary constraint states that the trace has `value` at the given `row` and `column`.
+#[derive(Debug, Clone)]
+pub struct BoundaryConstraint<F: IsField> {
+    pub row: usize,
+    pub column: usize,
+    pub value: FieldElement<F>,
+}
+
+/// Algebraic Intermediate Representation (AIR) of a program.
+///
+/// It describes the public parameters of the program, i.e. the trace dimensions along with
+/// the constraints that a valid trace must satisfy. The prover builds the composition polynomial
+/// from these, and the verifier evaluates the same constraints at the queried points.
+pub trait Air<F: IsField> {
+    /// Number of rows in the trace, such that `n + 1` is a power of two.
+    fn trace_length(&self) -> usize;
+
+    /// Number of columns in the trace.
+    fn width(&self) -> usize {
+        1
+    }
+
+    /// Constraints on the values of specific cells in the trace.
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>>;
+
+    /// Constraints that must evaluate to zero over every row of the trace (except the last few,
+    /// where the frame would go out of the trace).
+    fn transition_constraints(&self) -> Vec<AirExpr<F>>;
+
+    /// Number of consecutive rows that the transition constraints look at.
+    fn window(&self) -> usize {
+        self.transition_constraints()
+            .iter()
+            .map(|c| c.max_offset())
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Rows of the trace domain where transition constraints do not apply. Since the trace has `n` rows
+    /// over a domain of size `n + 1`, these are the last `window` rows of the domain.
+    fn transition_exemptions(&self) -> Vec<usize> {
+        let n = self.trace_length();
+        (n + 1 - self.window()..=n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AirExpr::*;
+    use super::*;
+    use crate::field::Stark101PrimeField as F;
+
+    #[test]
+    fn test_expr() {
+        let expr: AirExpr<F> = Trace(0, 2) - Trace(0, 1) * Trace(0, 1) - Trace(0, 0) * Trace(0, 0);
+        assert_eq!(expr.degree(), 2);
+        assert_eq!(expr.max_offset(), 2);
+
+        // 1, 2, 5 is a FibonacciSq frame
+        let frame = [1u64, 2, 5].map(|a| vec![FieldElement::<F>::from(a)]);
+        assert_eq!(expr.evaluate(&frame, &|c| *c), FieldElement::zero());
+
+        let expr = expr + Constant(FieldElement::from(3u64));
+        assert_eq!(expr.evaluate(&frame, &|c| *c), FieldElement::from(3u64));
+    }
+}
@@ -0,0 +1,126 @@
+pub use lambdaworks_math::field::fields::fft_friendly::babybear::Babybear31PrimeField;
+use lambdaworks_math::field::{
+    element::FieldElement,
+    extensions::quadratic::{HasQuadraticNonResidue, QuadraticExtensionField},
+};
+
+use crate::{
+    extension::ExtensionField,
+    fft::TwoAdicField,
+    hash::{Keccak256Transcript, Sha256Backend},
+};
+
+/// The BabyBear field with `p = 15 . 2^27 + 1`, which is tiny, so the challenges are sampled from its quartic
+/// extension with about 120 bits.
+impl TwoAdicField for Babybear31PrimeField {
+    const TWO_ADICITY: u64 = 27;
+    // 31^15
+    const TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY: u64 = 440564289;
+    const GENERATOR: u64 = 31;
+}
+
+pub type Babybear31PrimeFieldElement = FieldElement<Babybear31PrimeField>;
+
+/// Backend for Merkle Tree using Sha256, where each leaf is a row of field elements.
+pub type Babybear31PrimeFieldBackend = Sha256Backend<Babybear31PrimeField>;
+
+/// Transcript for Fiat-Shamir transform using Keccak256.
+pub type Babybear31PrimeFieldTranscript = Keccak256Transcript<Babybear31PrimeField>;
+
+/// `11` is the smallest non-square of the field, so `x^2 - 11` is irreducible.
+///
+/// LambdaWorks has `QuadraticBabybearField` as well, but its residue `-1` is a square as `4` divides `p - 1`.
+#[derive(Clone, Debug)]
+pub struct BabybearQuadraticResidue;
+
+impl HasQuadraticNonResidue<Babybear31PrimeField> for BabybearQuadraticResidue {
+    fn residue() -> FieldElement<Babybear31PrimeField> {
+        FieldElement::from(11u64)
+    }
+}
+
+/// Quadratic extension of the BabyBear field, i.e. `F[u] / (u^2 - 11)`.
+pub type BabybearQuadraticField =
+    QuadraticExtensionField<Babybear31PrimeField, BabybearQuadraticResidue>;
+
+/// `u` is not a square within the quadratic extension since its norm `-11` is not a square of the field, so
+/// `y^2 - u` is irreducible.
+#[derive(Clone, Debug)]
+pub struct BabybearQuarticResidue;
+
+impl HasQuadraticNonResidue<BabybearQuadraticField> for BabybearQuarticResidue {
+    fn residue() -> FieldElement<BabybearQuadraticField> {
+        FieldElement::new([FieldElement::zero(), FieldElement::one()])
+    }
+}
+
+/// Quartic extension of the BabyBear field as a tower, i.e. `F[u, v] / (u^2 - 11, v^2 - u)`.
+pub type BabybearQuarticField =
+    QuadraticExtensionField<BabybearQuadraticField, BabybearQuarticResidue>;
+
+impl ExtensionField<Babybear31PrimeField> for BabybearQuarticField {
+    const DEGREE: usize = 4;
+
+    fn embed(element: &Babybear31PrimeFieldElement) -> FieldElement<Self> {
+        FieldElement::new([BabybearQuadraticField::embed(element), FieldElement::zero()])
+    }
+
+    fn from_base_coeffs(coeffs: &[Babybear31PrimeFieldElement]) -> FieldElement<Self> {
+        FieldElement::new([
+            BabybearQuadraticField::from_base_coeffs(&coeffs[..2]),
+            BabybearQuadraticField::from_base_coeffs(&coeffs[2..4]),
+        ])
+    }
+
+    fn to_base_coeffs(element: &FieldElement<Self>) -> Vec<Babybear31PrimeFieldElement> {
+        element
+            .value()
+            .iter()
+            .flat_map(BabybearQuadraticField::to_base_coeffs)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Babybear31PrimeFieldElement as FE;
+    use super::*;
+
+    /// `p - 1`
+    const ORDER: u64 = 2013265920;
+
+    #[test]
+    fn test_generator() {
+        // p - 1 = 2^27 . 3 . 5
+        let g = FE::from(Babybear31PrimeField::GENERATOR);
+        for factor in [2, 3, 5] {
+            assert_ne!(g.pow(ORDER / factor), FE::one());
+        }
+
+        let root = Babybear31PrimeField::primitive_root_of_unity(27);
+        assert_eq!(root.pow(1u64 << 27), FE::one());
+        assert_ne!(root.pow(1u64 << 26), FE::one());
+    }
+
+    #[test]
+    fn test_non_residue() {
+        // Euler's criterion
+        assert_eq!(
+            BabybearQuadraticResidue::residue().pow(ORDER / 2),
+            -FE::one()
+        );
+        assert_eq!((-FE::one()).pow(ORDER / 2), FE::one());
+    }
+
+    #[test]
+    fn test_quartic_field() {
+        let coeffs = [1u64, 2, 3, 4].map(FE::from);
+        let x = BabybearQuarticField::from_base_coeffs(&coeffs);
+        assert_eq!(BabybearQuarticField::to_base_coeffs(&x), coeffs.to_vec());
+        assert_eq!(&x * x.inv().unwrap(), FieldElement::one());
+
+        // v^4 = u^2 = 11
+        let v = BabybearQuarticField::from_base_coeffs(&[0u64, 0, 1, 0].map(FE::from));
+        assert_eq!(v.pow(4u64), BabybearQuarticField::embed(&FE::from(11u64)));
+    }
+}
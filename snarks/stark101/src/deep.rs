@@ -0,0 +1,211 @@
+#![allow(clippy::too_many_arguments)]
+
+use lambdaworks_crypto::fiat_shamir::is_transcript::IsTranscript;
+use lambdaworks_math::field::{element::FieldElement, traits::IsField};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    extension::{sample_extension_element, ExtensionField},
+    parallel::{batch_inverse, into_iter, iter},
+};
+
+/// Samples an out-of-domain point `z` of the extension field from the transcript, that is outside of both the
+/// trace domain of size `n + 1` and the evaluation domain `w . H` of size `eval_domain_len`.
+///
+/// Since `g` is within `H`, the points `g^k . z` are outside of both domains as well, so that the DEEP quotients
+/// never divide by zero.
+pub fn sample_ood_point<F: IsField, E: ExtensionField<F>, T: IsTranscript<F>>(
+    channel: &mut T,
+    n: usize,
+    eval_domain_len: usize,
+    w: &FieldElement<F>,
+) -> FieldElement<E> {
+    let one = FieldElement::<E>::one();
+    let w_pow = E::embed(&w.pow(eval_domain_len));
+    loop {
+        let z = sample_extension_element::<F, E, T>(channel);
+        if z.pow(n + 1) != one && z.pow(eval_domain_len) != w_pow {
+            return z;
+        }
+    }
+}
+
+/// Evaluates the DEEP composition polynomial at `x`, given the evaluations of the trace columns `trace_row` and of
+/// the composition polynomial `cp_x` at `x`, along with their evaluations at the out-of-domain point `z`:
+///
+/// `D(x) = sum_(k, j) gamma_(k, j) . (f_j(x) - f_j(g^k . z)) / (x - g^k . z) + gamma . (cp(x) - cp(z)) / (x - z)`
+///
+/// where `ood_frame[k][j]` is `f_j(g^k . z)` and the last element of `gammas` is the one for the composition polynomial.
+/// Each quotient is a polynomial only if the claimed evaluations at `z` are correct, so FRI on `D` ties the
+/// composition polynomial to the trace at `z`. The trace is over the base field, and everything else is over the
+/// extension.
+pub fn evaluate_deep_composition<F: IsField, E: ExtensionField<F>>(
+    x: &FieldElement<F>,
+    trace_row: &[FieldElement<F>],
+    cp_x: &FieldElement<E>,
+    z: &FieldElement<E>,
+    g: &FieldElement<F>,
+    ood_frame: &[Vec<FieldElement<E>>],
+    ood_cp: &FieldElement<E>,
+    gammas: &[FieldElement<E>],
+) -> FieldElement<E> {
+    assert_eq!(gammas.len(), ood_frame.len() * trace_row.len() + 1);
+    let mut result = FieldElement::<E>::zero();
+    let mut gammas = gammas.iter();
+    let x = E::embed(x);
+    let g = E::embed(g);
+
+    let mut z_k = z.clone();
+    for ood_row in ood_frame {
+        let denom_inv = (&x - &z_k).inv().expect("x is not g^k . z");
+        for (f_x, f_z) in trace_row.iter().zip(ood_row) {
+            result += gammas.next().unwrap() * (E::embed(f_x) - f_z) * &denom_inv;
+        }
+        z_k *= &g;
+    }
+
+    let denom_inv = (x - z).inv().expect("x is not z");
+    result + gammas.next().unwrap() * (cp_x - ood_cp) * denom_inv
+}
+
+/// Evaluates the DEEP composition polynomial over the entire `domain`, the same as `evaluate_deep_composition` at
+/// each point but with the inverses computed in batch.
+///
+/// The trace evaluations are given as rows, such that `trace_eval[i][j]` is `f_j(domain[i])`.
+pub fn deep_composition_evals<F: IsField<BaseType: Send + Sync>, E: ExtensionField<F>>(
+    domain: &[FieldElement<F>],
+    trace_eval: &[Vec<FieldElement<F>>],
+    cp_eval: &[FieldElement<E>],
+    z: &FieldElement<E>,
+    g: &FieldElement<F>,
+    ood_frame: &[Vec<FieldElement<E>>],
+    ood_cp: &FieldElement<E>,
+    gammas: &[FieldElement<E>],
+) -> Vec<FieldElement<E>> {
+    let width = trace_eval[0].len();
+    assert_eq!(gammas.len(), ood_frame.len() * width + 1);
+    let domain = iter!(domain).map(E::embed).collect::<Vec<_>>();
+    let g = E::embed(g);
+
+    // the inverses of x - g^k . z for each row of the frame, and of x - z for the composition polynomial
+    let mut z_k = z.clone();
+    let mut denoms = Vec::with_capacity(ood_frame.len());
+    for _ in ood_frame {
+        let mut denom = iter!(domain).map(|x| x - &z_k).collect::<Vec<_>>();
+        batch_inverse(&mut denom).expect("x is not g^k . z");
+        denoms.push(denom);
+        z_k *= &g;
+    }
+    let mut cp_denom = iter!(domain).map(|x| x - z).collect::<Vec<_>>();
+    batch_inverse(&mut cp_denom).expect("x is not z");
+
+    let gamma = gammas.last().unwrap();
+    into_iter!(0..domain.len())
+        .map(|i| {
+            let mut result = FieldElement::<E>::zero();
+            for (k, ood_row) in ood_frame.iter().enumerate() {
+                for (j, f_z) in ood_row.iter().enumerate() {
+                    result += &gammas[k * width + j]
+                        * (E::embed(&trace_eval[i][j]) - f_z)
+                        * &denoms[k][i];
+                }
+            }
+            result + gamma * (&cp_eval[i] - ood_cp) * &cp_denom[i]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fft::{evaluate_offset_fft, interpolate_offset_fft, TwoAdicField},
+        field::{Stark101PrimeField as F, Stark101PrimeFieldElement as FE},
+    };
+    use lambdaworks_math::polynomial::Polynomial;
+
+    fn random_polynomial(num_coeffs: usize) -> Polynomial<FE> {
+        Polynomial::new(
+            &(0..num_coeffs)
+                .map(|_| FE::from(rand::random::<u64>()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_deep_composition() {
+        let (degree_bound, domain_size) = (16, 128);
+        let h = F::primitive_root_of_unity(7);
+        let w = FE::from(5u64);
+        let g = h.pow(8u64);
+        let domain = (0..domain_size).map(|i| w * h.pow(i)).collect::<Vec<_>>();
+
+        let f = [
+            random_polynomial(degree_bound),
+            random_polynomial(degree_bound),
+        ];
+        let cp = random_polynomial(degree_bound);
+        let f_columns = f
+            .iter()
+            .map(|f_j| evaluate_offset_fft(f_j, domain_size, &h, &w))
+            .collect::<Vec<_>>();
+        let trace_eval = (0..domain_size)
+            .map(|i| vec![f_columns[0][i], f_columns[1][i]])
+            .collect::<Vec<_>>();
+        let cp_eval = evaluate_offset_fft(&cp, domain_size, &h, &w);
+
+        let z = FE::from(7u64);
+        let ood_frame = (0..2)
+            .map(|k| {
+                f.iter()
+                    .map(|f_j| f_j.evaluate(&(g.pow(k as u64) * z)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let ood_cp = cp.evaluate(&z);
+        let gammas = (0..5).map(|i| FE::from(i as u64 + 2)).collect::<Vec<_>>();
+
+        let deep_eval = deep_composition_evals(
+            &domain,
+            &trace_eval,
+            &cp_eval,
+            &z,
+            &g,
+            &ood_frame,
+            &ood_cp,
+            &gammas,
+        );
+        for i in [0, 17, 127] {
+            let d_x = evaluate_deep_composition(
+                &domain[i],
+                &trace_eval[i],
+                &cp_eval[i],
+                &z,
+                &g,
+                &ood_frame,
+                &ood_cp,
+                &gammas,
+            );
+            assert_eq!(deep_eval[i], d_x);
+        }
+
+        // the quotients are polynomials with correct evaluations at z, and not otherwise
+        let deep = interpolate_offset_fft(&deep_eval, &h, &w);
+        assert!(deep.degree() < degree_bound - 1);
+
+        let wrong_cp = ood_cp + FE::one();
+        let deep_eval = deep_composition_evals(
+            &domain,
+            &trace_eval,
+            &cp_eval,
+            &z,
+            &g,
+            &ood_frame,
+            &wrong_cp,
+            &gammas,
+        );
+        let deep = interpolate_offset_fft(&deep_eval, &h, &w);
+        assert!(deep.degree() >= degree_bound);
+    }
+}
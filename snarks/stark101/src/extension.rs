@@ -0,0 +1,183 @@
+use std::fmt::Debug;
+
+use lambdaworks_crypto::fiat_shamir::is_transcript::IsTranscript;
+use lambdaworks_math::{
+    field::{
+        element::FieldElement,
+        extensions::quadratic::{HasQuadraticNonResidue, QuadraticExtensionField},
+        traits::IsField,
+    },
+    polynomial::Polynomial,
+};
+
+use crate::hash::FieldBytes;
+
+/// An extension field `E` of degree `DEGREE` over the base field `F`, where the challenges are sampled from.
+///
+/// The trace lives in the base field, but the randomness of the verifier (alphas, the out-of-domain point, gammas
+/// and betas) is drawn from the extension so that a small field still has enough bits of security. An element of
+/// the extension is a vector of `DEGREE` coefficients over the base field, which is how it is hashed and written
+/// into proofs.
+pub trait ExtensionField<F: IsField>: IsField<BaseType: Send + Sync> {
+    /// Degree of the extension, i.e. the number of base field coefficients of an element.
+    const DEGREE: usize;
+
+    /// Embeds an element of the base field into the extension.
+    fn embed(element: &FieldElement<F>) -> FieldElement<Self>;
+
+    /// Returns the element with the given `DEGREE` coefficients over the base field.
+    fn from_base_coeffs(coeffs: &[FieldElement<F>]) -> FieldElement<Self>;
+
+    /// Returns the `DEGREE` coefficients of `element` over the base field.
+    fn to_base_coeffs(element: &FieldElement<Self>) -> Vec<FieldElement<F>>;
+}
+
+/// Every field is an extension of degree 1 of itself, which is what we have without extension challenges.
+impl<F: IsField<BaseType: Send + Sync>> ExtensionField<F> for F {
+    const DEGREE: usize = 1;
+
+    fn embed(element: &FieldElement<F>) -> FieldElement<F> {
+        element.clone()
+    }
+
+    fn from_base_coeffs(coeffs: &[FieldElement<F>]) -> FieldElement<F> {
+        coeffs[0].clone()
+    }
+
+    fn to_base_coeffs(element: &FieldElement<F>) -> Vec<FieldElement<F>> {
+        vec![element.clone()]
+    }
+}
+
+impl<F, Q> ExtensionField<F> for QuadraticExtensionField<F, Q>
+where
+    F: IsField<BaseType: Send + Sync>,
+    Q: Clone + Debug + HasQuadraticNonResidue<F>,
+{
+    const DEGREE: usize = 2;
+
+    fn embed(element: &FieldElement<F>) -> FieldElement<Self> {
+        FieldElement::new([element.clone(), FieldElement::zero()])
+    }
+
+    fn from_base_coeffs(coeffs: &[FieldElement<F>]) -> FieldElement<Self> {
+        FieldElement::new([coeffs[0].clone(), coeffs[1].clone()])
+    }
+
+    fn to_base_coeffs(element: &FieldElement<Self>) -> Vec<FieldElement<F>> {
+        element.value().to_vec()
+    }
+}
+
+/// Samples an element of the extension, one base field coefficient at a time.
+pub fn sample_extension_element<F, E, T>(transcript: &mut T) -> FieldElement<E>
+where
+    F: IsField,
+    E: ExtensionField<F>,
+    T: IsTranscript<F>,
+{
+    let coeffs = (0..E::DEGREE)
+        .map(|_| transcript.sample_field_element())
+        .collect::<Vec<_>>();
+    E::from_base_coeffs(&coeffs)
+}
+
+/// Appends an element of the extension to the transcript, one base field coefficient at a time.
+pub fn append_extension_element<F, E, T>(transcript: &mut T, element: &FieldElement<E>)
+where
+    F: IsField,
+    E: ExtensionField<F>,
+    T: IsTranscript<F>,
+{
+    for coeff in E::to_base_coeffs(element) {
+        transcript.append_field_element(&coeff);
+    }
+}
+
+/// Flattens elements of the extension into their base field coefficients, e.g. to commit to them.
+pub fn flatten<F: IsField, E: ExtensionField<F>>(
+    elements: &[FieldElement<E>],
+) -> Vec<FieldElement<F>> {
+    elements.iter().flat_map(E::to_base_coeffs).collect()
+}
+
+/// Returns `sum_i e_i . values[i]`, where `e_i` is the element of the extension whose only nonzero base field
+/// coefficient is the `i`-th one, i.e. `E::from_base_coeffs(values)` when the values are in the base field.
+pub fn combine_coeffs<F: IsField, E: ExtensionField<F>>(
+    values: &[FieldElement<E>],
+) -> FieldElement<E> {
+    assert_eq!(values.len(), E::DEGREE, "wrong number of coefficients");
+    values
+        .iter()
+        .enumerate()
+        .fold(FieldElement::zero(), |acc, (i, v)| {
+            let mut e_i = vec![FieldElement::<F>::zero(); E::DEGREE];
+            e_i[i] = FieldElement::one();
+            acc + E::from_base_coeffs(&e_i) * v
+        })
+}
+
+/// Evaluates a polynomial over the base field at a point of the extension.
+pub fn evaluate_at<F: IsField, E: ExtensionField<F>>(
+    poly: &Polynomial<FieldElement<F>>,
+    z: &FieldElement<E>,
+) -> FieldElement<E> {
+    poly.coefficients
+        .iter()
+        .rev()
+        .fold(FieldElement::zero(), |acc, c| acc * z + E::embed(c))
+}
+
+/// Number of bits of the extension field, i.e. `DEGREE . floor(log2(p))`, which bounds the soundness of the
+/// challenges that are sampled from it.
+pub fn field_bits<F: FieldBytes, E: ExtensionField<F>>() -> usize {
+    E::DEGREE * F::field_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{
+        Stark101PrimeField as F, Stark101PrimeFieldElement as FE, Stark101PrimeFieldExtension as E,
+    };
+
+    #[test]
+    fn test_embed() {
+        let a = FE::from(3u64);
+        let b = FieldElement::<E>::new([FE::from(1u64), FE::from(2u64)]);
+        assert_eq!(E::embed(&a) * &b, a * b.clone());
+        let coeffs: Vec<FE> = E::to_base_coeffs(&b);
+        assert_eq!(coeffs, vec![FE::from(1u64), FE::from(2u64)]);
+        assert_eq!(E::from_base_coeffs(&coeffs), b);
+        assert_eq!(field_bits::<F, E>(), 62);
+        assert_eq!(field_bits::<F, F>(), 31);
+    }
+
+    #[test]
+    fn test_evaluate_at() {
+        let poly = Polynomial::new(&[1u64, 2, 3].map(FE::from));
+        let z = FieldElement::<E>::new([FE::from(4u64), FE::from(5u64)]);
+        let expected =
+            E::embed(&FE::from(1u64)) + FE::from(2u64) * z.clone() + FE::from(3u64) * z.square();
+        assert_eq!(evaluate_at(&poly, &z), expected);
+
+        // over the base field, this is the usual evaluation
+        let x = FE::from(6u64);
+        assert_eq!(evaluate_at::<F, F>(&poly, &x), poly.evaluate(&x));
+    }
+
+    #[test]
+    fn test_combine_coeffs() {
+        // m(z) = m_0(z) + e_1 . m_1(z) for polynomials over the base field, and m(x) has coefficients m_i(x)
+        let m = [[1u64, 2], [3, 4]].map(|coeffs| Polynomial::new(&coeffs.map(FE::from)));
+        let e_1 = FieldElement::<E>::new([FE::zero(), FE::one()]);
+        let z = FieldElement::<E>::new([FE::from(4u64), FE::from(5u64)]);
+        let m_z = m.each_ref().map(|m_i| evaluate_at::<F, E>(m_i, &z));
+        assert_eq!(combine_coeffs::<F, E>(&m_z), &m_z[0] + e_1 * &m_z[1]);
+
+        let x = FE::from(6u64);
+        let m_x = m.each_ref().map(|m_i| E::embed(&m_i.evaluate(&x)));
+        let coeffs = m.each_ref().map(|m_i| m_i.evaluate(&x));
+        assert_eq!(combine_coeffs::<F, E>(&m_x), E::from_base_coeffs(&coeffs));
+    }
+}
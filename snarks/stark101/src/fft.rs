@@ -0,0 +1,231 @@
+use lambdaworks_math::{
+    field::{element::FieldElement, traits::IsField},
+    polynomial::Polynomial,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    hash::FieldBytes,
+    parallel::{chunks_mut, into_iter, iter, iter_mut},
+};
+
+/// A field whose multiplicative group has a large subgroup of order `2^TWO_ADICITY`, so that
+/// we can use radix-2 FFTs over it.
+///
+/// This mirrors `IsFFTField` of LambdaWorks, which we can not implement for our fields because
+/// they are type aliases of LambdaWorks's own field types.
+pub trait TwoAdicField: IsField {
+    /// Largest `k` such that `2^k` divides the multiplicative group order.
+    const TWO_ADICITY: u64;
+    /// A primitive root of unity of order `2^TWO_ADICITY`.
+    const TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY: u64;
+    /// A generator of the multiplicative group, which is not within any proper subgroup and is therefore used
+    /// as the offset of the evaluation coset.
+    const GENERATOR: u64;
+
+    /// Returns a primitive root of unity of order `2^log_order`.
+    ///
+    /// ## Panics
+    ///
+    /// If `log_order` exceeds the two-adicity of the field.
+    fn primitive_root_of_unity(log_order: u64) -> FieldElement<Self> {
+        assert!(
+            log_order <= Self::TWO_ADICITY,
+            "order exceeds the two-adicity"
+        );
+        FieldElement::<Self>::from(Self::TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY)
+            .pow(1u64 << (Self::TWO_ADICITY - log_order))
+    }
+}
+
+/// A field that the STARK works over, that is with radix-2 FFTs and a canonical encoding of its elements, which
+/// can be shared across threads as the Merkle trees require.
+pub trait StarkField: TwoAdicField + FieldBytes + IsField<BaseType: Send + Sync> {}
+
+impl<F: TwoAdicField + FieldBytes + IsField<BaseType: Send + Sync>> StarkField for F {}
+
+/// Reorders the elements of `values` by bit-reversing their indices.
+fn bit_reverse_permute<T>(values: &mut [T]) {
+    let n = values.len();
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+/// The butterfly of the FFT, i.e. `(a, b) -> (a + w . b, a - w . b)`.
+fn butterfly<F: IsField>(a: &mut FieldElement<F>, b: &mut FieldElement<F>, w: &FieldElement<F>) {
+    let t = &*b * w;
+    *b = &*a - &t;
+    *a = &*a + t;
+}
+
+/// Evaluates the polynomial with `coeffs` over the subgroup generated by `root`, i.e. returns
+/// `p(root^i)` for `i = 0..n` where `n` is the number of coefficients and the order of `root`.
+///
+/// This is the iterative Cooley-Tukey FFT, which first bit-reverses the input and then
+/// applies the butterflies from the smallest to the largest sub-FFTs. With the `parallel` feature, the sub-FFTs
+/// are done in parallel while there are many of them, and the butterflies of each one otherwise.
+pub fn fft<F: IsField<BaseType: Send + Sync>>(
+    coeffs: &[FieldElement<F>],
+    root: &FieldElement<F>,
+) -> Vec<FieldElement<F>> {
+    let n = coeffs.len();
+    assert_eq!(n.count_ones(), 1, "size must be a power of 2");
+
+    let mut values = coeffs.to_vec();
+    bit_reverse_permute(&mut values);
+
+    let mut len = 2;
+    while len <= n {
+        let half = len >> 1;
+        let w_len = root.pow(n / len); // root of unity of order `len`
+        let twiddles = (0..half)
+            .scan(FieldElement::<F>::one(), |w, _| {
+                let current = w.clone();
+                *w = &*w * &w_len;
+                Some(current)
+            })
+            .collect::<Vec<_>>();
+
+        if n / len >= half {
+            chunks_mut!(values, len).for_each(|chunk| {
+                let (lo, hi) = chunk.split_at_mut(half);
+                for ((a, b), w) in lo.iter_mut().zip(hi.iter_mut()).zip(&twiddles) {
+                    butterfly(a, b, w);
+                }
+            });
+        } else {
+            for chunk in values.chunks_mut(len) {
+                let (lo, hi) = chunk.split_at_mut(half);
+                iter_mut!(lo)
+                    .zip(iter_mut!(hi))
+                    .zip(iter!(twiddles))
+                    .for_each(|((a, b), w)| butterfly(a, b, w));
+            }
+        }
+        len <<= 1;
+    }
+
+    values
+}
+
+/// Interpolates the evaluations `evals` over the subgroup generated by `root`, returning the coefficients.
+///
+/// The inverse FFT is the FFT with `root^-1`, scaled by `1/n`.
+pub fn ifft<F: IsField<BaseType: Send + Sync>>(
+    evals: &[FieldElement<F>],
+    root: &FieldElement<F>,
+) -> Vec<FieldElement<F>> {
+    let n_inv = FieldElement::<F>::from(evals.len() as u64)
+        .inv()
+        .expect("size is not zero");
+    let root_inv = root.inv().expect("root is not zero");
+    into_iter!(fft(evals, &root_inv))
+        .map(|c| c * &n_inv)
+        .collect()
+}
+
+/// Evaluates `poly` over the coset `offset . <root>` of size `domain_size`, i.e. the low-degree extension.
+///
+/// We have `p(offset . x) = sum_i (c_i . offset^i) x^i`, so it is enough to scale the coefficients and
+/// evaluate over the subgroup.
+pub fn evaluate_offset_fft<F: IsField<BaseType: Send + Sync>>(
+    poly: &Polynomial<FieldElement<F>>,
+    domain_size: usize,
+    root: &FieldElement<F>,
+    offset: &FieldElement<F>,
+) -> Vec<FieldElement<F>> {
+    assert!(
+        poly.coefficients.len() <= domain_size,
+        "polynomial degree is too high for the domain"
+    );
+    let mut coeffs = Vec::with_capacity(domain_size);
+    let mut offset_pow = FieldElement::<F>::one();
+    for c in &poly.coefficients {
+        coeffs.push(c * &offset_pow);
+        offset_pow *= offset;
+    }
+    coeffs.resize(domain_size, FieldElement::zero());
+
+    fft(&coeffs, root)
+}
+
+/// Interpolates the evaluations `evals` over the coset `offset . <root>`, the inverse of `evaluate_offset_fft`.
+pub fn interpolate_offset_fft<F: IsField<BaseType: Send + Sync>>(
+    evals: &[FieldElement<F>],
+    root: &FieldElement<F>,
+    offset: &FieldElement<F>,
+) -> Polynomial<FieldElement<F>> {
+    let offset_inv = offset.inv().expect("offset is not zero");
+    let mut offset_pow = FieldElement::<F>::one();
+    let coeffs = ifft(evals, root)
+        .into_iter()
+        .map(|c| {
+            let c = c * &offset_pow;
+            offset_pow = &offset_pow * &offset_inv;
+            c
+        })
+        .collect::<Vec<_>>();
+
+    Polynomial::new(&coeffs)
+}
+
+/// Interpolates the evaluations `evals` over the subgroup generated by `root`.
+pub fn interpolate_fft<F: IsField<BaseType: Send + Sync>>(
+    evals: &[FieldElement<F>],
+    root: &FieldElement<F>,
+) -> Polynomial<FieldElement<F>> {
+    Polynomial::new(&ifft(evals, root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Stark101PrimeField as F, Stark101PrimeFieldElement as FE};
+
+    #[test]
+    fn test_root_of_unity() {
+        let root = F::primitive_root_of_unity(F::TWO_ADICITY);
+        assert_eq!(root.pow(1u64 << 30), FE::one());
+        assert_ne!(root.pow(1u64 << 29), FE::one());
+
+        let root = F::primitive_root_of_unity(10);
+        assert_eq!(root.pow(1024u64), FE::one());
+        assert_ne!(root.pow(512u64), FE::one());
+    }
+
+    #[test]
+    fn test_fft() {
+        let n = 64;
+        let coeffs = (0..n)
+            .map(|_| FE::from(rand::random::<u64>()))
+            .collect::<Vec<_>>();
+        let poly = Polynomial::new(&coeffs);
+        let root = F::primitive_root_of_unity(6);
+
+        let evals = fft(&coeffs, &root);
+        for (i, eval) in evals.iter().enumerate() {
+            assert_eq!(*eval, poly.evaluate(&root.pow(i)));
+        }
+        assert_eq!(interpolate_fft(&evals, &root), poly);
+    }
+
+    #[test]
+    fn test_offset_fft() {
+        let poly = Polynomial::new(&[1u64, 2, 3, 4, 5].map(FE::from));
+        let root = F::primitive_root_of_unity(4);
+        let offset = FE::from(5u64);
+
+        let evals = evaluate_offset_fft(&poly, 16, &root, &offset);
+        for (i, eval) in evals.iter().enumerate() {
+            assert_eq!(*eval, poly.evaluate(&(offset * root.pow(i))));
+        }
+        assert_eq!(interpolate_offset_fft(&evals, &root, &offset), poly);
+    }
+}
@@ -1,16 +1,20 @@
-use lambdaworks_crypto::{
-    fiat_shamir::default_transcript::DefaultTranscript,
-    merkle_tree::backends::types::Sha2_256Backend,
-};
+use lambdaworks_crypto::fiat_shamir::default_transcript::DefaultTranscript;
 use lambdaworks_math::{
     field::{
         element::FieldElement,
+        extensions::quadratic::{HasQuadraticNonResidue, QuadraticExtensionField},
         fields::montgomery_backed_prime_fields::{IsModulus, U64PrimeField},
     },
     unsigned_integer::element::U64,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    fft::TwoAdicField,
+    hash::{DigestBackend, DigestTranscript},
+    poseidon::{PoseidonBackend, PoseidonTranscript},
+};
+
 #[derive(Clone, Debug, Hash, Copy, Serialize, Deserialize)]
 pub struct MontgomeryConfigStark101PrimeField;
 impl IsModulus<U64> for MontgomeryConfigStark101PrimeField {
@@ -19,56 +23,71 @@ impl IsModulus<U64> for MontgomeryConfigStark101PrimeField {
 
 pub type Stark101PrimeField = U64PrimeField<MontgomeryConfigStark101PrimeField>;
 
-/// Backend for Merkle Tree using Sha256.
-pub type Stark101PrimeFieldBackend = Sha2_256Backend<Stark101PrimeField>;
+/// Backend for Merkle Tree using Sha256, where each leaf is a row of field elements.
+pub type Stark101PrimeFieldBackend = DigestBackend<Stark101PrimeField, sha2::Sha256>;
 
 /// Transcript for Fiat-Shamir transform using Stark101PrimeField.
 pub type Stark101PrimeFieldTranscript = DefaultTranscript<Stark101PrimeField>;
 
-// impl IsFFTField for Stark101PrimeField {
-//     const TWO_ADICITY: u64 = 30;
-//     // Change this line for a new function like `from_limbs`.
-//     const TWO_ADIC_PRIMITVE_ROOT_OF_UNITY: U64 = UnsignedInteger::from_hex_unchecked(
-//         "5282db87529cfa3f0464519c8b0fa5ad187148e11a61616070024f42f8ef94", // TODO: !!!
-//     );
+/// Backend for Merkle Tree using Keccak256, where each leaf is a row of field elements.
+pub type Stark101PrimeFieldKeccakBackend = DigestBackend<Stark101PrimeField, sha3::Keccak256>;
+
+/// Backend for Merkle Tree using Blake3, where each leaf is a row of field elements.
+pub type Stark101PrimeFieldBlake3Backend = DigestBackend<Stark101PrimeField, blake3::Hasher>;
+
+/// Backend for Merkle Tree using Poseidon, where each leaf is a row of field elements.
+pub type Stark101PrimeFieldPoseidonBackend = PoseidonBackend;
+
+/// Transcript for Fiat-Shamir transform using Sha256.
+pub type Stark101PrimeFieldSha256Transcript = DigestTranscript<Stark101PrimeField, sha2::Sha256>;
+
+/// Transcript for Fiat-Shamir transform using Blake3.
+pub type Stark101PrimeFieldBlake3Transcript = DigestTranscript<Stark101PrimeField, blake3::Hasher>;
 
-//     fn field_name() -> &'static str {
-//         "stark101"
-//     }
-// }
+/// Transcript for Fiat-Shamir transform using Poseidon.
+pub type Stark101PrimeFieldPoseidonTranscript = PoseidonTranscript;
+
+/// The primitive root `5` is not a square, so `x^2 - 5` is irreducible.
+impl HasQuadraticNonResidue<Stark101PrimeField> for MontgomeryConfigStark101PrimeField {
+    fn residue() -> FieldElement<Stark101PrimeField> {
+        FieldElement::from(PRIMITIVE_ROOT)
+    }
+}
+
+/// Quadratic extension of the Stark101 field, i.e. `F[x] / (x^2 - 5)`, to sample the challenges from.
+pub type Stark101PrimeFieldExtension =
+    QuadraticExtensionField<Stark101PrimeField, MontgomeryConfigStark101PrimeField>;
+
+impl TwoAdicField for Stark101PrimeField {
+    const TWO_ADICITY: u64 = 30;
+    // 5 is a primitive root, so 5^3 has order 2^30
+    const TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY: u64 = 125;
+    const GENERATOR: u64 = PRIMITIVE_ROOT;
+}
 
 pub type Stark101PrimeFieldElement = FieldElement<Stark101PrimeField>;
 
-/// Returns a generator for a subgroup of the given order a power of two.
+/// Order of the multiplicative group of the field, i.e. `p - 1`.
+const MULTIPLICATIVE_ORDER: u128 = 3u128 * (1u128 << 30u128);
+
+/// A primitive root of the field, i.e. a generator of its multiplicative group.
+const PRIMITIVE_ROOT: u64 = 5;
+
+/// Returns a primitive root of unity of the given order, i.e. a generator for the subgroup of that order.
 ///
-/// 1. Generate a random element `r` in the field.
-/// 2. Compute `g = r^(order_u128 / order)` (co-factor clearing).
-/// 3. If `g^order == 1` and `g^(order >> 1) != 1`, return `g`.
-/// The second check ensures that the order of `g` is exactly `order`, and not that of
-/// some smaller sub-group.
+/// This is computed as `5^((p - 1) / order)` where `5` is a primitive root of the field, so the same
+/// generator is returned every time and the verifier can compute it as well.
 ///
 /// ## Panics
 ///
 /// If the order does not divide the multiplicative field order.
-pub fn get_subgroup_generator(order: u128) -> Stark101PrimeFieldElement {
-    let order_u128: u128 = 3u128 * (1u128 << 30u128);
+pub fn primitive_root_of_unity(order: u128) -> Stark101PrimeFieldElement {
     assert!(
-        order_u128 % order == 0,
+        MULTIPLICATIVE_ORDER.is_multiple_of(order),
         "order must divide the multiplicative field order"
     );
 
-    loop {
-        let r = Stark101PrimeFieldElement::from(rand::random::<u64>());
-
-        // co-factor clearing
-        let g = r.pow(order_u128 / order);
-
-        if g.pow(order) == Stark101PrimeFieldElement::one()
-            && g.pow(order >> 1) != Stark101PrimeFieldElement::one()
-        {
-            return g;
-        }
-    }
+    Stark101PrimeFieldElement::from(PRIMITIVE_ROOT).pow(MULTIPLICATIVE_ORDER / order)
 }
 
 /// Given a generator `g`, generates the group that it belongs to.
@@ -85,18 +104,12 @@ pub fn generate_subgroup(g: Stark101PrimeFieldElement) -> Vec<Stark101PrimeField
     subgroup
 }
 
-/// Generates a generator of the group, by sampling a random element and making sure
-/// that it does not belong to a smaller subgroup.
-pub fn generate_generator() -> Stark101PrimeFieldElement {
-    loop {
-        let r = Stark101PrimeFieldElement::from(rand::random::<u64>());
-
-        if r.pow(3u128) != Stark101PrimeFieldElement::one()
-            && r.pow(1u128 << 30u128) != Stark101PrimeFieldElement::one()
-        {
-            return r;
-        }
-    }
+/// Returns the offset of the coset that the trace is extended to.
+///
+/// This is the primitive root `5` itself, which does not belong to any proper subgroup, so the coset
+/// is disjoint from the trace domain.
+pub fn coset_offset() -> Stark101PrimeFieldElement {
+    Stark101PrimeFieldElement::from(PRIMITIVE_ROOT)
 }
 
 #[cfg(test)]
@@ -115,7 +128,7 @@ mod tests {
     #[test]
     fn test_subgroup_1024() {
         let order = 1024u128;
-        let g = get_subgroup_generator(order);
+        let g = primitive_root_of_unity(order);
         let subgroup = generate_subgroup(g);
         assert_eq!(subgroup.len(), order as usize);
     }
@@ -123,7 +136,7 @@ mod tests {
     #[test]
     fn test_subgroup_8192() {
         let order = 8192u128;
-        let g = get_subgroup_generator(order);
+        let g = primitive_root_of_unity(order);
         let subgroup = generate_subgroup(g);
         assert_eq!(subgroup.len(), order as usize);
     }
@@ -135,9 +148,25 @@ mod tests {
     }
 
     #[test]
-    fn test_generator() {
-        let order_u128: u128 = 3u128 * (1u128 << 30u128);
-        let g = generate_generator();
-        assert_eq!(g.pow(order_u128), FE::one());
+    fn test_subgroup_3() {
+        let subgroup = generate_subgroup(primitive_root_of_unity(3));
+        assert_eq!(subgroup.len(), 3);
+    }
+
+    #[test]
+    fn test_coset_offset() {
+        // the offset generates the entire multiplicative group
+        let w = coset_offset();
+        assert_eq!(w.pow(MULTIPLICATIVE_ORDER), FE::one());
+        assert_ne!(w.pow(MULTIPLICATIVE_ORDER / 2), FE::one());
+        assert_ne!(w.pow(MULTIPLICATIVE_ORDER / 3), FE::one());
+    }
+
+    #[test]
+    fn test_two_adic_root() {
+        assert_eq!(
+            primitive_root_of_unity(1 << 30),
+            FE::from(Stark101PrimeField::TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY)
+        );
     }
 }
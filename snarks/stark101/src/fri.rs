@@ -1,274 +1,565 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
 use lambdaworks_crypto::{
-    fiat_shamir::is_transcript::IsTranscript,
-    merkle_tree::{backends::types::Sha2_256Backend, merkle::MerkleTree},
+    fiat_shamir::is_transcript::IsTranscript, merkle_tree::merkle::MerkleTree,
 };
 use lambdaworks_math::{
     field::{element::FieldElement, traits::IsField},
     polynomial::Polynomial,
-    traits::AsBytes,
 };
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-use crate::proof::Stark101Decommitment;
-
-/// We use a constant blowup factor in this example.
-pub const BLOWUP_FACTOR: usize = 8;
+use crate::{
+    extension::{append_extension_element, flatten, sample_extension_element, ExtensionField},
+    hash::{MerkleBackend, MerkleNode},
+    merkle::{LeafSalts, MerkleMultiProof},
+    options::StarkOptions,
+    parallel::{batch_inverse, into_iter, iter},
+    proof::Stark101Decommitment,
+};
 
-/// Given a domain of length `n`, returns the first half of it with each element squared.
-pub fn next_fri_domain<F: IsField>(domain: Vec<FieldElement<F>>) -> Vec<FieldElement<F>> {
-    domain
-        .iter()
-        .take(domain.len() >> 1)
-        .map(|x| x.square())
+/// Given a domain of length `n`, returns the first `n / folding_factor` elements of it, each raised to
+/// the power `folding_factor`.
+pub fn next_fri_domain<F: IsField<BaseType: Send + Sync>>(
+    domain: Vec<FieldElement<F>>,
+    folding_factor: usize,
+) -> Vec<FieldElement<F>> {
+    iter!(domain[..domain.len() / folding_factor])
+        .map(|x| x.pow(folding_factor))
         .collect()
 }
 
 /// Given a polynomial `poly` and a field element `beta`, returns the folding operator applied to `poly`.
 ///
-/// What happens here is that `poly` is split into even and odd coefficients, and the odd part is multiplied
-/// by `beta`, therefore reducing the degree of the polynomial by half.
+/// What happens here is that `poly` is split into `folding_factor` polynomials `p_i` by its coefficients, such that
+/// `poly(x) = p_0(x^k) + x . p_1(x^k) + ... + x^(k-1) . p_(k-1)(x^k)`, and these are combined with powers of `beta`,
+/// therefore reducing the degree of the polynomial by a factor of `k`. For `k = 2`, this is `even + beta . odd`.
 pub fn next_fri_polynomial<F: IsField>(
     poly: Polynomial<FieldElement<F>>,
     beta: FieldElement<F>,
+    folding_factor: usize,
 ) -> Polynomial<FieldElement<F>> {
-    let even_coeffs = poly
-        .coefficients
-        .clone()
-        .into_iter()
-        .step_by(2)
-        .collect::<Vec<_>>();
-    let even = Polynomial::new(&even_coeffs);
-
-    let odd_coeffs = poly
-        .coefficients
-        .clone()
-        .into_iter()
-        .skip(1)
-        .step_by(2)
-        .collect::<Vec<_>>();
-    let odd = Polynomial::new(&odd_coeffs);
+    let mut next_poly = Polynomial::zero();
+    let mut beta_pow = FieldElement::<F>::one();
+    for i in 0..folding_factor {
+        let coeffs = poly
+            .coefficients
+            .clone()
+            .into_iter()
+            .skip(i)
+            .step_by(folding_factor)
+            .collect::<Vec<_>>();
+        next_poly = next_poly + Polynomial::new(&coeffs) * beta_pow.clone();
+        beta_pow *= &beta;
+    }
 
-    even + beta * odd
+    next_poly
 }
 
 /// Given a polynomial `poly` and an evaluation domain `domain` along with a
 /// random field element `beta`, returns the next FRI layer.
 ///
-/// This next layer contains the evaluations of the folded polynomial over the squared half-domain.
-pub fn next_fri_layer<F: IsField>(
+/// This next layer contains the evaluations of the folded polynomial over the next domain.
+pub fn next_fri_layer<F: IsField<BaseType: Send + Sync>>(
     poly: Polynomial<FieldElement<F>>,
     domain: Vec<FieldElement<F>>,
     beta: FieldElement<F>,
+    folding_factor: usize,
 ) -> (
     Polynomial<FieldElement<F>>,
     Vec<FieldElement<F>>,
     Vec<FieldElement<F>>,
 ) {
-    let next_poly = next_fri_polynomial(poly, beta);
-    let next_domain = next_fri_domain(domain);
-    let next_layer = next_domain
-        .iter()
+    let next_poly = next_fri_polynomial(poly, beta, folding_factor);
+    let next_domain = next_fri_domain(domain, folding_factor);
+    let next_layer = iter!(next_domain)
         .map(|x| next_poly.evaluate(x))
         .collect::<Vec<_>>();
 
     (next_poly, next_domain, next_layer)
 }
 
-/// Commits to the given polynomial `cp` and returns the FRI layers along with their Merkle trees.
-pub fn fri_commit<F: IsField, T: IsTranscript<F>>(
-    cp: Polynomial<FieldElement<F>>,
+/// Folds the evaluations `coset` of a FRI layer at the points `x . z^t` for `t = 0..k`, where `z` is a primitive
+/// `k`-th root of unity, to find the evaluation of the next layer at `x^k`.
+///
+/// With `poly(x) = p_0(x^k) + x . p_1(x^k) + ... + x^(k-1) . p_(k-1)(x^k)`, we have
+/// `p_s(x^k) = x^(-s) / k . sum_t z^(-s . t) . poly(x . z^t)`, and the next layer is `sum_s beta^s . p_s(x^k)`.
+/// For `k = 2`, this is `(poly(x) + poly(-x)) / 2 + beta . (poly(x) - poly(-x)) / (2x)`.
+///
+/// Both `x^(-1)` and `z^(-1)` are given as inverses, so that they can be computed in batch.
+pub fn fold_coset<F: IsField>(
+    coset: &[FieldElement<F>],
+    x_inv: &FieldElement<F>,
+    z_inv: &FieldElement<F>,
+    beta: &FieldElement<F>,
+) -> FieldElement<F> {
+    let k = coset.len();
+    let k_inv = FieldElement::<F>::from(k as u64)
+        .inv()
+        .expect("folding factor is not zero");
+
+    let mut next = FieldElement::<F>::zero();
+    let mut scale = k_inv; // beta^s . x^(-s) / k
+    let mut z_inv_s = FieldElement::<F>::one(); // z^(-s)
+    for _ in 0..k {
+        let (sum, _) = coset.iter().fold(
+            (FieldElement::<F>::zero(), FieldElement::<F>::one()),
+            |(sum, z_pow), eval| (sum + &z_pow * eval, z_pow * &z_inv_s),
+        );
+        next += &scale * sum;
+        scale = scale * beta * x_inv;
+        z_inv_s *= z_inv;
+    }
+
+    next
+}
+
+/// Given the evaluations `layer` of a FRI layer over `domain` and a random field element `beta`,
+/// returns the next domain and the next FRI layer, without going through the coefficients.
+///
+/// The element at index `j + t . n / k` of the domain is `x_j . z^t`, so the coset of `x_j` is
+/// found at stride `n / k` within the layer.
+pub fn next_fri_layer_evals<F: IsField<BaseType: Send + Sync>>(
+    layer: &[FieldElement<F>],
     domain: Vec<FieldElement<F>>,
-    cp_eval: Vec<FieldElement<F>>,
-    cp_merkle: MerkleTree<Sha2_256Backend<F>>,
+    beta: &FieldElement<F>,
+    folding_factor: usize,
+) -> (Vec<FieldElement<F>>, Vec<FieldElement<F>>) {
+    let stride = layer.len() / folding_factor;
+    let z_inv = &domain[0] * domain[stride].inv().expect("domain has no zeros");
+
+    let mut x_invs = domain[..stride].to_vec();
+    batch_inverse(&mut x_invs).expect("domain has no zeros");
+    let next_layer = iter!(x_invs)
+        .enumerate()
+        .map(|(j, x_inv)| {
+            let coset = (0..folding_factor)
+                .map(|t| layer[j + t * stride].clone())
+                .collect::<Vec<_>>();
+            fold_coset(&coset, x_inv, &z_inv, beta)
+        })
+        .collect::<Vec<_>>();
+    let next_domain = next_fri_domain(domain, folding_factor);
+
+    (next_domain, next_layer)
+}
+
+/// Groups the evaluations of a FRI layer into the leaves of its Merkle tree, such that each leaf is a coset
+/// `x . z^t` for `t = 0..k`, i.e. `(f(x), f(-x))` for `k = 2`. The leaf at index `j` is the coset of the
+/// `j`-th point, so that a single leaf is opened to fold a query.
+pub fn fri_layer_leaves<F: IsField<BaseType: Send + Sync>>(
+    layer: &[FieldElement<F>],
+    folding_factor: usize,
+) -> Vec<Vec<FieldElement<F>>> {
+    let stride = layer.len() / folding_factor;
+    into_iter!(0..stride)
+        .map(|j| {
+            (0..folding_factor)
+                .map(|t| layer[j + t * stride].clone())
+                .collect()
+        })
+        .collect()
+}
+
+/// Identifier of the trace Merkle tree, for the salts.
+pub const TRACE_TREE: usize = 0;
+
+/// Identifier of the composition polynomial Merkle tree, for the salts.
+pub const COMPOSITION_TREE: usize = 1;
+
+/// Identifier of the Merkle tree of the FRI layer at index `i`, for the salts.
+pub fn fri_tree(i: usize) -> usize {
+    i + 2
+}
+
+/// Merkle commits to the FRI layer at index `i`, with a (salted) coset at each leaf. The layer is over the
+/// extension field, so each leaf has the coefficients of the coset over the base field.
+pub fn fri_layer_commit<
+    F: IsField<BaseType: Send + Sync>,
+    E: ExtensionField<F>,
+    B: MerkleBackend<F>,
+>(
+    layer: &[FieldElement<E>],
+    folding_factor: usize,
+    salts: &LeafSalts,
+    i: usize,
+) -> MerkleTree<B> {
+    let leaves = into_iter!(fri_layer_leaves(layer, folding_factor))
+        .map(|coset| flatten::<F, E>(&coset))
+        .collect::<Vec<_>>();
+    MerkleTree::<B>::build(&salts.apply(fri_tree(i), &leaves))
+}
+
+/// Commits to the composition polynomial given only its evaluations `cp_eval` over `domain`, and returns
+/// the FRI domains and layers along with their Merkle trees.
+///
+/// This is the same as `fri_commit`, but it folds the evaluations directly with `fold_coset` instead of
+/// folding the coefficients and evaluating them again, so it results in the same layers and transcript.
+/// The last layer is not committed, as it is a constant that is sent as is.
+///
+/// Within the prover, the polynomial that is committed this way is the DEEP composition polynomial.
+pub fn fri_commit_evals<
+    F: IsField<BaseType: Send + Sync>,
+    E: ExtensionField<F>,
+    B: MerkleBackend<F>,
+    T: IsTranscript<F>,
+>(
+    domain: Vec<FieldElement<E>>,
+    cp_eval: Vec<FieldElement<E>>,
+    cp_merkle: MerkleTree<B>,
+    salts: &LeafSalts,
+    options: &StarkOptions,
+    channel: &mut T,
+) -> (
+    Vec<Vec<FieldElement<E>>>,
+    Vec<Vec<FieldElement<E>>>,
+    Vec<MerkleTree<B>>,
+) {
+    let num_folds = options.num_fri_folds(domain.len() / options.blowup_factor - 1);
+    let mut fri_domains = vec![domain];
+    let mut fri_layers = vec![cp_eval];
+    let mut fri_merkles = vec![cp_merkle];
+
+    for i in 0..num_folds {
+        // sample randomness
+        let beta = sample_extension_element::<F, E, T>(channel);
+
+        // apply FRI operator
+        let (next_domain, next_layer) = next_fri_layer_evals(
+            fri_layers.last().unwrap(),
+            fri_domains.last().unwrap().clone(),
+            &beta,
+            options.fri_folding_factor,
+        );
+        fri_domains.push(next_domain);
+        fri_layers.push(next_layer);
+
+        // commit to layer & add root to transcript, unless it is the last one
+        if i + 1 < num_folds {
+            let tree = fri_layer_commit::<F, E, B>(
+                fri_layers.last().unwrap(),
+                options.fri_folding_factor,
+                salts,
+                i + 1,
+            );
+            channel.append_bytes(&tree.root.to_bytes());
+            fri_merkles.push(tree);
+        }
+    }
+
+    // the last layer must be constant, add it to transcript
+    let last_layer = fri_layers.last().unwrap();
+    assert!(
+        last_layer.iter().all(|eval| *eval == last_layer[0]),
+        "last FRI layer is not constant"
+    );
+    append_extension_element::<F, E, T>(channel, &last_layer[0]);
+
+    (fri_domains, fri_layers, fri_merkles)
+}
+
+/// Commits to the given polynomial `cp` and returns the FRI layers along with their Merkle trees.
+///
+/// Folding is applied `options.num_fri_folds(n)` times, at which point the polynomial must be a constant.
+/// We do not stop at the first constant polynomial, so that the number of layers only depends on the
+/// domain size and the verifier knows it in advance. The last layer is not committed, as it is a constant
+/// that is sent as is.
+pub fn fri_commit<
+    F: IsField<BaseType: Send + Sync>,
+    E: ExtensionField<F>,
+    B: MerkleBackend<F>,
+    T: IsTranscript<F>,
+>(
+    cp: Polynomial<FieldElement<E>>,
+    domain: Vec<FieldElement<E>>,
+    cp_eval: Vec<FieldElement<E>>,
+    cp_merkle: MerkleTree<B>,
+    salts: &LeafSalts,
+    options: &StarkOptions,
     channel: &mut T,
 ) -> (
-    Vec<Polynomial<FieldElement<F>>>,
-    Vec<Vec<FieldElement<F>>>,
-    Vec<Vec<FieldElement<F>>>,
-    Vec<MerkleTree<Sha2_256Backend<F>>>,
-)
-where
-    FieldElement<F>: AsBytes + Send + Sync,
-{
+    Vec<Polynomial<FieldElement<E>>>,
+    Vec<Vec<FieldElement<E>>>,
+    Vec<Vec<FieldElement<E>>>,
+    Vec<MerkleTree<B>>,
+) {
+    let num_folds = options.num_fri_folds(domain.len() / options.blowup_factor - 1);
     let mut fri_polys = vec![cp];
     let mut fri_domains = vec![domain];
     let mut fri_layers = vec![cp_eval];
     let mut fri_merkles = vec![cp_merkle];
 
     // apply FRI until you end up with a constant polynomial
-    while fri_polys.last().unwrap().degree() > 0 {
+    for i in 0..num_folds {
         // sample randomness
-        let beta = channel.sample_field_element();
+        let beta = sample_extension_element::<F, E, T>(channel);
 
         // apply FRI operator
         let (next_poly, next_domain, next_layer) = next_fri_layer(
             fri_polys.last().unwrap().clone(),
             fri_domains.last().unwrap().clone(),
             beta,
+            options.fri_folding_factor,
         );
         fri_polys.push(next_poly);
         fri_domains.push(next_domain);
-        fri_layers.push(next_layer.clone());
+        fri_layers.push(next_layer);
 
-        // commit to layer & add root to transcript
-        let tree = MerkleTree::<Sha2_256Backend<F>>::build(&next_layer);
-        channel.append_bytes(&tree.root);
-        fri_merkles.push(tree);
+        // commit to layer & add root to transcript, unless it is the last one
+        if i + 1 < num_folds {
+            let tree = fri_layer_commit::<F, E, B>(
+                fri_layers.last().unwrap(),
+                options.fri_folding_factor,
+                salts,
+                i + 1,
+            );
+            channel.append_bytes(&tree.root.to_bytes());
+            fri_merkles.push(tree);
+        }
     }
 
     // add constant polynomial to transcript
     assert_eq!(fri_polys.last().unwrap().degree(), 0);
-    channel.append_field_element(&fri_layers.last().unwrap()[0]);
+    append_extension_element::<F, E, T>(channel, &fri_layers.last().unwrap()[0]);
 
     (fri_polys, fri_domains, fri_layers, fri_merkles)
 }
 
-/// Decommits on FRI layers, providing the evaluations of the polynomial at the given index and its sibling along with
-/// Merkle authentication paths.
+/// Decommits on FRI layers, providing the evaluations of the polynomial at the coset of the given index,
+/// which is a single leaf of each layer. The coset of `x` consists of the `folding_factor` points that
+/// map to the same point `x^k` within the next layer, i.e. `x`, `-x` for `k = 2`.
 ///
-/// For this example in particular, it provides the following:
-/// - `cp_0(x)` and its path
-/// - `cp_0(-x)` and its path
-/// - `cp_1(x^2)` and its path
-/// - `cp_1(-x^2)` and its path
-/// - `cp_2(x^4)` and its path
-/// - `cp_2(-x^4)` and its path
+/// For this example in particular with `k = 2`, it provides the following:
+/// - `cp_0(x)` and `cp_0(-x)`
+/// - `cp_1(x^2)` and `cp_1(-x^2)`
+/// - `cp_2(x^4)` and `cp_2(-x^4)`
 /// - ...
-/// - `cp_10(x^1024)` and its path
-pub fn decommit_on_fri_layers<F: IsField, T: IsTranscript<F>>(
+/// - `cp_9(x^512)` and `cp_9(-x^512)`
+///
+/// where the points in the coset are ordered by their index within the layer. The last layer is a constant,
+/// so it is not decommitted. Returns the index of the opened leaf for each layer.
+pub fn decommit_on_fri_layers<F: IsField>(
     idx: usize,
-    channel: &mut T,
+    folding_factor: usize,
     evals: &mut Vec<FieldElement<F>>,
-    paths: &mut Vec<Vec<[u8; 32]>>,
     fri_layers: &[Vec<FieldElement<F>>],
-    fri_merkles: &[MerkleTree<Sha2_256Backend<F>>],
-) where
-    FieldElement<F>: AsBytes + Send + Sync,
-{
-    for i in 0..fri_layers.len() - 1 {
-        let layer = fri_layers[i].clone();
-        let merkle = fri_merkles[i].clone();
-
-        let length = layer.len();
-        let idx = idx % length; // idx is always in the first half of the layer
-        let sib_idx = (idx + (length >> 1)) % length; // sibling idx is in the other half
-
-        // cp_i(x^{2(i+1)}), e.g. cp_2(x^4)
-        let eval = &layer[idx];
-        channel.append_field_element(eval);
-        evals.push(eval.clone());
-        let auth_path = merkle.get_proof_by_pos(idx).unwrap();
-        for path in &auth_path.merkle_path {
-            channel.append_bytes(path);
-        }
-        paths.push(auth_path.merkle_path);
-
-        // cp_i(-x^{2(i+1)}), e.g. cp_2(-x^4)
-        let eval = &layer[sib_idx];
-        channel.append_field_element(eval);
-        evals.push(eval.clone());
-        let auth_path = merkle.get_proof_by_pos(sib_idx).unwrap();
-        for path in &auth_path.merkle_path {
-            channel.append_bytes(path);
+) -> Vec<usize> {
+    let mut leaf_indices = Vec::new();
+    for layer in &fri_layers[..fri_layers.len() - 1] {
+        let stride = layer.len() / folding_factor;
+        let base_idx = (idx % layer.len()) % stride; // index of the coset within the first part of the layer
+        for t in 0..folding_factor {
+            // cp_i(x^{2^i}), cp_i(-x^{2^i}), ... e.g. cp_2(x^4) and cp_2(-x^4)
+            evals.push(layer[base_idx + t * stride].clone());
         }
-        paths.push(auth_path.merkle_path);
+        leaf_indices.push(base_idx);
     }
 
-    channel.append_field_element(&fri_layers.last().unwrap()[0]);
+    leaf_indices
 }
 
-/// Decommits on an FRI query. Since our CP makes use of `x`, `g . x` and `g^2 . x`, we need to decommit on these
-/// three points. However, due to the domain extension, these points are `BLOWUP_FACTOR` apart from each other.
+/// Decommits on an FRI query at `x`. With DEEP, the verifier computes the DEEP composition polynomial at `x` from
+/// the trace and the composition polynomial at `x` alone, so unlike the tutorial we do not need `g . x`, `g^2 . x`
+/// and so on; the constraints are checked at the out-of-domain point instead.
 ///
-/// Within this function, we first provide Merkle proofs to the evaluations of the polynomial at these points.
-/// That is, we provide the things below:
-///
-/// - `f(x)` and its path
-/// - `f(g . x)` and its path
-/// - `f(g^2 . x)` and its path
-///
-/// Then, we call `decommit_on_layers` to provide the rest of decommitment.
-pub fn decommit_on_query<F: IsField, T: IsTranscript<F>>(
+/// Within this function, we first provide the row of the trace at `x`, which contains the evaluations of all trace
+/// columns, and the evaluation of the composition polynomial at `x`. Then, we call `decommit_on_fri_layers` to
+/// provide the rest of decommitment. The Merkle proofs are not provided here, instead the indices of the opened
+/// leaves are returned for each FRI layer (the trace and the composition polynomial are opened at `idx`), so
+/// that they can be opened together over all queries. In zero-knowledge mode, the salts of the opened leaves are
+/// provided as well.
+pub fn decommit_on_query<F: IsField, E: ExtensionField<F>>(
     idx: usize,
-    channel: &mut T,
-    evals: &mut Vec<FieldElement<F>>,
-    paths: &mut Vec<Vec<[u8; 32]>>,
-    f_eval: &[FieldElement<F>],
-    f_merkle: &MerkleTree<Sha2_256Backend<F>>,
-    fri_layers: &[Vec<FieldElement<F>>],
-    fri_merkles: &[MerkleTree<Sha2_256Backend<F>>],
-) where
-    FieldElement<F>: AsBytes + Send + Sync,
-{
-    assert!(idx + 2 * BLOWUP_FACTOR < f_eval.len(), "index out-of-range");
-
-    // f(x)
-    let eval = &f_eval[idx];
-    channel.append_field_element(eval);
-    evals.push(eval.clone());
-    let auth_path = f_merkle.get_proof_by_pos(idx).unwrap();
-    for path in &auth_path.merkle_path {
-        channel.append_bytes(path);
-    }
-    paths.push(auth_path.merkle_path);
-
-    // f(g . x)
-    let eval = &f_eval[idx + BLOWUP_FACTOR];
-    channel.append_field_element(eval);
-    evals.push(eval.clone());
-    let auth_path = f_merkle.get_proof_by_pos(idx + BLOWUP_FACTOR).unwrap();
-    for path in &auth_path.merkle_path {
-        channel.append_bytes(path);
-    }
-    paths.push(auth_path.merkle_path);
-
-    // f(g^2 . x)
-    let eval = &f_eval[idx + 2 * BLOWUP_FACTOR];
-    channel.append_field_element(eval);
-    evals.push(eval.clone());
-    let auth_path = f_merkle.get_proof_by_pos(idx + 2 * BLOWUP_FACTOR).unwrap();
-    for path in &auth_path.merkle_path {
-        channel.append_bytes(path);
+    options: &StarkOptions,
+    decommitment: &mut Stark101Decommitment<F, E>,
+    trace_eval: &[Vec<FieldElement<F>>],
+    cp_eval: &[FieldElement<E>],
+    fri_layers: &[Vec<FieldElement<E>>],
+    salts: &LeafSalts,
+) -> Vec<usize> {
+    assert!(idx < trace_eval.len(), "index out-of-range");
+
+    // (f_0(x), f_1(x), ...) and cp(x)
+    decommitment.trace_row = trace_eval[idx].clone();
+    decommitment.cp_eval = cp_eval[idx].clone();
+
+    let fri_indices = decommit_on_fri_layers(
+        idx,
+        options.fri_folding_factor,
+        &mut decommitment.evals,
+        fri_layers,
+    );
+
+    if options.zero_knowledge {
+        decommitment.salts.push(salts.salt(TRACE_TREE, idx));
+        decommitment.salts.push(salts.salt(COMPOSITION_TREE, idx));
+        for (i, leaf) in fri_indices.iter().enumerate() {
+            decommitment.salts.push(salts.salt(fri_tree(i), *leaf));
+        }
     }
-    paths.push(auth_path.merkle_path);
 
-    decommit_on_fri_layers(idx, channel, evals, paths, fri_layers, fri_merkles);
+    fri_indices
+}
+
+/// Samples `num_queries` query indices below `upper_bound` from the transcript.
+///
+/// Sampling an index does not change the state of the transcript, so each index is appended to it afterwards,
+/// otherwise all queries would end up at the same index.
+pub fn sample_query_indices<F: IsField, T: IsTranscript<F>>(
+    channel: &mut T,
+    num_queries: usize,
+    upper_bound: u64,
+) -> Vec<usize> {
+    (0..num_queries)
+        .map(|_| {
+            let idx = channel.sample_u64(upper_bound);
+            channel.append_bytes(&idx.to_be_bytes());
+            idx as usize
+        })
+        .collect()
 }
 
 /// Generate `num_queries` random queries and decommits on those indices.
 /// The queries are sampled from the transcript, i.e. they are "sent" by
 /// the verifier.
-pub fn decommit_fri<F: IsField, T: IsTranscript<F>>(
-    num_queries: usize,
+///
+/// The Merkle proofs of all queries are batched, so that there is a single [`MerkleMultiProof`] for the trace,
+/// for the composition polynomial and for each committed FRI layer. The indices are sampled first, after which the
+/// queries are independent and are decommitted in parallel with the `parallel` feature.
+pub fn decommit_fri<
+    F: IsField<BaseType: Send + Sync>,
+    E: ExtensionField<F>,
+    B: MerkleBackend<F>,
+    T: IsTranscript<F>,
+>(
+    options: &StarkOptions,
     channel: &mut T,
-    f_eval: &[FieldElement<F>],
-    f_merkle: &MerkleTree<Sha2_256Backend<F>>,
-    fri_layers: &[Vec<FieldElement<F>>],
-    fri_merkles: &[MerkleTree<Sha2_256Backend<F>>],
-) -> Vec<Stark101Decommitment<F>>
-where
-    FieldElement<F>: AsBytes + Send + Sync,
-{
-    let upper_bound = (f_eval.len() - 2 * BLOWUP_FACTOR) as u64;
-    let mut decommitments = Vec::new();
-    for _ in 0..num_queries {
-        let mut evals = Vec::new();
-        let mut paths = Vec::new();
-        let random_idx = channel.sample_u64(upper_bound);
-        decommit_on_query(
-            random_idx as usize,
-            channel,
-            &mut evals,
-            &mut paths,
-            f_eval,
-            f_merkle,
-            fri_layers,
-            fri_merkles,
+    trace_eval: &[Vec<FieldElement<F>>],
+    trace_merkle: &MerkleTree<B>,
+    cp_eval: &[FieldElement<E>],
+    cp_merkle: &MerkleTree<B>,
+    fri_layers: &[Vec<FieldElement<E>>],
+    fri_merkles: &[MerkleTree<B>],
+    salts: &LeafSalts,
+) -> (
+    Vec<Stark101Decommitment<F, E>>,
+    MerkleMultiProof<B::Node>,
+    MerkleMultiProof<B::Node>,
+    Vec<MerkleMultiProof<B::Node>>,
+) {
+    let indices =
+        sample_query_indices::<F, T>(channel, options.num_queries, trace_eval.len() as u64);
+
+    let (decommitments, leaves): (Vec<_>, Vec<_>) = iter!(indices)
+        .map(|&idx| {
+            let mut decommitment = Stark101Decommitment {
+                trace_row: Vec::new(),
+                cp_eval: FieldElement::zero(),
+                evals: Vec::new(),
+                salts: Vec::new(),
+            };
+            let leaves = decommit_on_query(
+                idx,
+                options,
+                &mut decommitment,
+                trace_eval,
+                cp_eval,
+                fri_layers,
+                salts,
+            );
+            (decommitment, leaves)
+        })
+        .unzip();
+
+    let mut fri_indices = vec![Vec::new(); fri_merkles.len()];
+    for leaves in leaves {
+        for (layer_indices, leaf) in fri_indices.iter_mut().zip(leaves) {
+            layer_indices.push(leaf);
+        }
+    }
+
+    let trace_proof = MerkleMultiProof::new(trace_merkle, &indices);
+    let cp_proof = MerkleMultiProof::new(cp_merkle, &indices);
+    let fri_proofs = fri_merkles
+        .iter()
+        .zip(&fri_indices)
+        .map(|(merkle, indices)| MerkleMultiProof::new(merkle, indices))
+        .collect();
+
+    (decommitments, trace_proof, cp_proof, fri_proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fft::{evaluate_offset_fft, TwoAdicField},
+        field::{
+            Stark101PrimeField as F, Stark101PrimeFieldBackend, Stark101PrimeFieldElement as FE,
+            Stark101PrimeFieldPoseidonBackend, Stark101PrimeFieldTranscript,
+        },
+    };
+
+    fn commit_both_ways<B: MerkleBackend<F> + Clone>(folding_factor: usize) {
+        let options = StarkOptions {
+            fri_folding_factor: folding_factor,
+            ..StarkOptions::default()
+        };
+        let (degree_bound, domain_size) = (64, 64 * options.blowup_factor);
+        let cp = Polynomial::new(
+            &(0..degree_bound)
+                .map(|_| FE::from(rand::random::<u64>()))
+                .collect::<Vec<_>>(),
+        );
+        let h = F::primitive_root_of_unity(domain_size.trailing_zeros() as u64);
+        let w = FE::from(5u64);
+        let domain = (0..domain_size).map(|i| w * h.pow(i)).collect::<Vec<_>>();
+        let cp_eval = evaluate_offset_fft(&cp, domain_size, &h, &w);
+        let salts = LeafSalts::new(true);
+        let cp_merkle = fri_layer_commit::<F, F, B>(&cp_eval, folding_factor, &salts, 0);
+
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        let (_, _, layers, merkles) = fri_commit::<F, F, B, _>(
+            cp,
+            domain.clone(),
+            cp_eval.clone(),
+            cp_merkle.clone(),
+            &salts,
+            &options,
+            &mut channel,
+        );
+
+        let mut channel_evals = Stark101PrimeFieldTranscript::default();
+        let (_, layers_evals, merkles_evals) = fri_commit_evals::<F, F, B, _>(
+            domain,
+            cp_eval,
+            cp_merkle,
+            &salts,
+            &options,
+            &mut channel_evals,
         );
 
-        decommitments.push(Stark101Decommitment { evals, paths });
+        assert_eq!(layers, layers_evals);
+        assert_eq!(
+            merkles.iter().map(|m| &m.root).collect::<Vec<_>>(),
+            merkles_evals.iter().map(|m| &m.root).collect::<Vec<_>>()
+        );
+        assert_eq!(channel.state(), channel_evals.state());
+    }
+
+    #[test]
+    fn test_fri_commit_evals() {
+        commit_both_ways::<Stark101PrimeFieldBackend>(2);
     }
 
-    decommitments
+    #[test]
+    fn test_fri_commit_evals_folding4() {
+        commit_both_ways::<Stark101PrimeFieldBackend>(4);
+    }
+
+    #[test]
+    fn test_fri_commit_evals_poseidon() {
+        commit_both_ways::<Stark101PrimeFieldPoseidonBackend>(2);
+    }
 }
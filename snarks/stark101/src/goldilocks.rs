@@ -0,0 +1,56 @@
+use lambdaworks_math::field::element::FieldElement;
+pub use lambdaworks_math::field::fields::u64_goldilocks_field::{
+    Goldilocks64ExtensionField, Goldilocks64Field,
+};
+
+use crate::{
+    fft::TwoAdicField,
+    hash::{FieldBytes, Keccak256Transcript, Sha256Backend},
+};
+
+/// The Goldilocks field with `p = 2^64 - 2^32 + 1`, whose quadratic extension `F[x] / (x^2 - 7)` has about
+/// 128 bits.
+impl TwoAdicField for Goldilocks64Field {
+    const TWO_ADICITY: u64 = 32;
+    // 7^((p - 1) / 2^32)
+    const TWO_ADIC_PRIMITIVE_ROOT_OF_UNITY: u64 = 1753635133440165772;
+    const GENERATOR: u64 = 7;
+}
+
+impl FieldBytes for Goldilocks64Field {
+    fn to_canonical_u64(element: &FieldElement<Self>) -> u64 {
+        element.representative()
+    }
+}
+
+pub type Goldilocks64FieldElement = FieldElement<Goldilocks64Field>;
+
+/// Backend for Merkle Tree using Sha256, where each leaf is a row of field elements.
+pub type Goldilocks64FieldBackend = Sha256Backend<Goldilocks64Field>;
+
+/// Transcript for Fiat-Shamir transform using Keccak256.
+pub type Goldilocks64FieldTranscript = Keccak256Transcript<Goldilocks64Field>;
+
+#[cfg(test)]
+mod tests {
+    use super::Goldilocks64FieldElement as FE;
+    use super::*;
+
+    #[test]
+    fn test_two_adic_root() {
+        let root = Goldilocks64Field::primitive_root_of_unity(32);
+        assert_eq!(root.pow(1u64 << 32), FE::one());
+        assert_ne!(root.pow(1u64 << 31), FE::one());
+    }
+
+    #[test]
+    fn test_generator() {
+        // p - 1 = 2^32 . 3 . 5 . 17 . 257 . 65537
+        let g = FE::from(Goldilocks64Field::GENERATOR);
+        let order = u64::MAX - (1u64 << 32) + 1;
+        for factor in [2, 3, 5, 17, 257, 65537] {
+            assert_ne!(g.pow(order / factor), FE::one());
+        }
+        assert_eq!(g.pow(order), FE::one());
+    }
+}
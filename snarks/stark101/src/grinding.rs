@@ -0,0 +1,63 @@
+use sha2::{Digest, Sha256};
+
+/// Hashes the transcript `state` together with the `nonce`, i.e. `SHA256(state || nonce)`.
+fn pow_hash(state: &[u8; 32], nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(state);
+    hasher.update(nonce.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Number of leading zero bits of `hash`.
+fn leading_zeros(hash: &[u8; 32]) -> usize {
+    let mut zeros = 0;
+    for byte in hash {
+        zeros += byte.leading_zeros() as usize;
+        if *byte != 0 {
+            break;
+        }
+    }
+    zeros
+}
+
+/// Finds the smallest nonce such that `SHA256(state || nonce)` has at least `grinding_bits` leading zero bits.
+///
+/// This takes about `2^grinding_bits` hashes, so that a malicious prover has to spend that much work for each
+/// attempt at finding favorable queries, which adds `grinding_bits` bits of security.
+pub fn grind(state: &[u8; 32], grinding_bits: usize) -> u64 {
+    (0..u64::MAX)
+        .find(|&nonce| is_valid_nonce(state, nonce, grinding_bits))
+        .expect("nonce not found")
+}
+
+/// Checks that `SHA256(state || nonce)` has at least `grinding_bits` leading zero bits.
+pub fn is_valid_nonce(state: &[u8; 32], nonce: u64, grinding_bits: usize) -> bool {
+    leading_zeros(&pow_hash(state, nonce)) >= grinding_bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leading_zeros() {
+        let mut hash = [0u8; 32];
+        assert_eq!(leading_zeros(&hash), 256);
+
+        hash[1] = 0b0001_0000;
+        assert_eq!(leading_zeros(&hash), 11);
+
+        hash[0] = 0b1000_0000;
+        assert_eq!(leading_zeros(&hash), 0);
+    }
+
+    #[test]
+    fn test_grinding() {
+        let state = [42u8; 32];
+        assert_eq!(grind(&state, 0), 0);
+
+        let nonce = grind(&state, 10);
+        assert!(is_valid_nonce(&state, nonce, 10));
+        assert!((0..nonce).all(|n| !is_valid_nonce(&state, n, 10)));
+    }
+}
@@ -0,0 +1,253 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use lambdaworks_crypto::{
+    fiat_shamir::is_transcript::IsTranscript, merkle_tree::traits::IsMerkleTreeBackend,
+};
+use lambdaworks_math::{
+    field::{
+        element::FieldElement,
+        fields::montgomery_backed_prime_fields::{IsModulus, MontgomeryBackendPrimeField},
+        traits::IsField,
+    },
+    unsigned_integer::element::U64,
+};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Canonical encoding of the elements of a field of at most 64 bits, which is what we hash and write into proofs.
+///
+/// LambdaWorks's `ByteConversion` is not implemented for every field we use (e.g. it panics for Goldilocks), so an
+/// element is written as its canonical representative, i.e. within `[0, p)`, as a big-endian `u64`.
+pub trait FieldBytes: IsField {
+    /// Canonical representative of `element`.
+    fn to_canonical_u64(element: &FieldElement<Self>) -> u64;
+
+    /// Encodes `element` in 8 bytes.
+    fn to_bytes(element: &FieldElement<Self>) -> [u8; 8] {
+        Self::to_canonical_u64(element).to_be_bytes()
+    }
+
+    /// Decodes an element encoded with `to_bytes`, returning `None` if the encoding is not canonical.
+    fn from_bytes(bytes: &[u8]) -> Option<FieldElement<Self>> {
+        let value = u64::from_be_bytes(bytes.try_into().ok()?);
+        let element = FieldElement::<Self>::from(value);
+        (Self::to_canonical_u64(&element) == value).then_some(element)
+    }
+
+    /// Number of bits of the modulus `p`, rounded down, i.e. `floor(log2(p))`.
+    fn field_bits() -> usize {
+        let p_minus_one = Self::to_canonical_u64(&-FieldElement::<Self>::one());
+        63 - p_minus_one.leading_zeros() as usize
+    }
+}
+
+impl<M> FieldBytes for MontgomeryBackendPrimeField<M, 1>
+where
+    M: IsModulus<U64> + Clone + std::fmt::Debug,
+{
+    fn to_canonical_u64(element: &FieldElement<Self>) -> u64 {
+        element.representative().limbs[0]
+    }
+}
+
+/// A node of a Merkle tree, which can be written to the transcript and to the binary proof as bytes.
+pub trait MerkleNode: Clone + PartialEq + Eq + Send + Sync + Debug {
+    /// Number of bytes of an encoded node.
+    const NUM_BYTES: usize;
+
+    /// Bits of collision resistance of the hash function, i.e. half of the bits of a node by the birthday bound.
+    const COLLISION_BITS: usize;
+
+    /// Encodes the node in exactly `NUM_BYTES` bytes.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Decodes a node that was encoded with `to_bytes`, returning `None` if the encoding is not canonical.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<const N: usize> MerkleNode for [u8; N] {
+    const NUM_BYTES: usize = N;
+    const COLLISION_BITS: usize = 4 * N;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+/// A Merkle tree backend whose leaves are rows of field elements, e.g. a trace row or a FRI coset.
+///
+/// This is implemented for `DigestBackend` and `PoseidonBackend`, as well as for the backends of LambdaWorks such
+/// as `BatchSha2_256Backend`.
+pub trait MerkleBackend<F: IsField>:
+    IsMerkleTreeBackend<Data = Vec<FieldElement<F>>, Node: MerkleNode>
+{
+    /// Bits of collision resistance of the commitments, which bounds the security of a proof.
+    const COLLISION_BITS: usize = <Self::Node as MerkleNode>::COLLISION_BITS;
+}
+
+impl<F: IsField, B> MerkleBackend<F> for B where
+    B: IsMerkleTreeBackend<Data = Vec<FieldElement<F>>, Node: MerkleNode>
+{
+}
+
+/// Backend for Merkle Tree over any hash function with a 32-byte output that implements `Digest`, such as SHA256,
+/// Keccak256 or Blake3, where each leaf is a row of field elements.
+///
+/// This is the same as `FieldElementVectorBackend` of LambdaWorks, except that the elements are hashed in their
+/// canonical encoding, see `FieldBytes`.
+pub struct DigestBackend<F, D> {
+    phantom: PhantomData<(F, D)>,
+}
+
+impl<F, D> Default for DigestBackend<F, D> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, D> Clone for DigestBackend<F, D> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<F: FieldBytes, D: Digest> IsMerkleTreeBackend for DigestBackend<F, D>
+where
+    FieldElement<F>: Send + Sync,
+{
+    type Node = [u8; 32];
+    type Data = Vec<FieldElement<F>>;
+
+    fn hash_data(leaf: &Vec<FieldElement<F>>) -> [u8; 32] {
+        let mut hasher = D::new();
+        for element in leaf {
+            hasher.update(F::to_bytes(element));
+        }
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        hash
+    }
+
+    fn hash_new_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = D::new();
+        hasher.update(left);
+        hasher.update(right);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        hash
+    }
+}
+
+/// Backend for Merkle Tree using Sha256, where each leaf is a row of field elements.
+pub type Sha256Backend<F> = DigestBackend<F, Sha256>;
+
+/// A transcript over any hash function with a 32-byte output that implements `Digest`, such as SHA256,
+/// Keccak256 or Blake3.
+///
+/// This works the same way as `DefaultTranscript` of LambdaWorks, which is fixed to Keccak256, except that the
+/// field elements are appended in their canonical encoding, see `FieldBytes`.
+pub struct DigestTranscript<F: IsField, D: Digest + Clone> {
+    hasher: D,
+    phantom: PhantomData<F>,
+}
+
+impl<F: FieldBytes, D: Digest + Clone> DigestTranscript<F, D> {
+    pub fn new(data: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: D::new(),
+            phantom: PhantomData,
+        };
+        transcript.append_bytes(data);
+        transcript
+    }
+
+    /// Returns the current hash and absorbs it, so that the next sample is different.
+    fn sample(&mut self) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        result.copy_from_slice(&std::mem::replace(&mut self.hasher, D::new()).finalize());
+        result.reverse();
+        Digest::update(&mut self.hasher, result);
+        result
+    }
+}
+
+impl<F: FieldBytes, D: Digest + Clone> Default for DigestTranscript<F, D> {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl<F: FieldBytes, D: Digest + Clone> IsTranscript<F> for DigestTranscript<F, D> {
+    fn append_field_element(&mut self, element: &FieldElement<F>) {
+        self.append_bytes(&F::to_bytes(element));
+    }
+
+    fn append_bytes(&mut self, new_bytes: &[u8]) {
+        Digest::update(&mut self.hasher, new_bytes);
+    }
+
+    fn state(&self) -> [u8; 32] {
+        let mut state = [0u8; 32];
+        state.copy_from_slice(&self.hasher.clone().finalize());
+        state
+    }
+
+    fn sample_field_element(&mut self) -> FieldElement<F> {
+        FieldElement::from(u64::from_be_bytes(self.sample()[..8].try_into().unwrap()))
+    }
+
+    fn sample_u64(&mut self, upper_bound: u64) -> u64 {
+        u64::from_be_bytes(self.state()[..8].try_into().unwrap()) % upper_bound
+    }
+}
+
+/// Transcript for Fiat-Shamir transform using Keccak256, the same as `DefaultTranscript` for our fields.
+pub type Keccak256Transcript<F> = DigestTranscript<F, Keccak256>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Stark101PrimeField as F, Stark101PrimeFieldTranscript};
+
+    #[test]
+    fn test_node_bytes() {
+        let node = [7u8; 32];
+        assert_eq!(<[u8; 32]>::from_bytes(&node.to_bytes()), Some(node));
+        assert_eq!(<[u8; 32]>::from_bytes(&[7u8; 31]), None);
+    }
+
+    #[test]
+    fn test_field_bytes() {
+        let element = FieldElement::<F>::from(42u64);
+        assert_eq!(F::from_bytes(&F::to_bytes(&element)), Some(element));
+        assert_eq!(F::field_bits(), 31);
+
+        // p = 0xc0000001 is not canonical
+        assert_eq!(F::from_bytes(&0xc0000001u64.to_be_bytes()), None);
+    }
+
+    #[test]
+    fn test_digest_transcript() {
+        // with Keccak256, this is the same as the default transcript of LambdaWorks
+        let mut transcript = Keccak256Transcript::<F>::default();
+        let mut default = Stark101PrimeFieldTranscript::default();
+        fn append<T: IsTranscript<F>>(channel: &mut T) {
+            channel.append_bytes(b"stark101");
+            channel.append_field_element(&FieldElement::from(42u64));
+        }
+        append(&mut transcript);
+        append(&mut default);
+        assert_eq!(transcript.state(), default.state());
+        assert_eq!(
+            transcript.sample_field_element(),
+            default.sample_field_element()
+        );
+        assert_eq!(transcript.sample_u64(1000), default.sample_u64(1000));
+    }
+}
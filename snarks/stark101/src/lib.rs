@@ -1,4 +1,18 @@
+pub mod air;
+pub mod babybear;
+pub mod deep;
+pub mod extension;
+pub mod fft;
 pub mod field;
 pub mod fri;
+pub mod goldilocks;
+pub mod grinding;
+pub mod hash;
+pub mod merkle;
+pub mod options;
+pub mod parallel;
+pub mod poseidon;
 pub mod program;
 pub mod proof;
+pub mod prover;
+pub mod verify;
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use lambdaworks_crypto::merkle_tree::{merkle::MerkleTree, traits::IsMerkleTreeBackend};
+use lambdaworks_math::field::{element::FieldElement, traits::IsField};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::parallel::iter;
+
+/// Number of random field elements appended to each leaf in zero-knowledge mode.
+pub const SALT_LENGTH: usize = 4;
+
+/// Salts for the leaves of the Merkle trees, so that the roots and the multi-proofs do not reveal anything
+/// about the leaves that are not opened.
+///
+/// The salts are derived from a random seed as `SHA256(seed || tree || index || i)` for `i = 0..SALT_LENGTH`,
+/// so that the prover does not have to keep them around until the decommitment. Without a seed, i.e. when
+/// zero-knowledge is not needed, the salts are empty and the leaves are as they are.
+#[derive(Debug, Clone, Default)]
+pub struct LeafSalts {
+    seed: Option<[u8; 32]>,
+}
+
+impl LeafSalts {
+    /// Creates random salts if `zero_knowledge` is set, and empty salts otherwise.
+    pub fn new(zero_knowledge: bool) -> Self {
+        Self {
+            seed: zero_knowledge.then(rand::random),
+        }
+    }
+
+    /// Returns the salt of the leaf at `index` within the tree with the given `tree` identifier.
+    pub fn salt<F: IsField>(&self, tree: usize, index: usize) -> Vec<FieldElement<F>> {
+        let Some(seed) = self.seed else {
+            return Vec::new();
+        };
+        (0..SALT_LENGTH)
+            .map(|i| {
+                let mut hasher = Sha256::new();
+                hasher.update(seed);
+                hasher.update((tree as u64).to_be_bytes());
+                hasher.update((index as u64).to_be_bytes());
+                hasher.update((i as u64).to_be_bytes());
+                let hash: [u8; 32] = hasher.finalize().into();
+                FieldElement::<F>::from(u64::from_be_bytes(hash[..8].try_into().unwrap()))
+            })
+            .collect()
+    }
+
+    /// Appends the salt of each leaf within the tree with the given `tree` identifier.
+    pub fn apply<F: IsField<BaseType: Send + Sync>>(
+        &self,
+        tree: usize,
+        leaves: &[Vec<FieldElement<F>>],
+    ) -> Vec<Vec<FieldElement<F>>> {
+        iter!(leaves)
+            .enumerate()
+            .map(|(index, leaf)| [leaf.clone(), self.salt(tree, index)].concat())
+            .collect()
+    }
+}
+
+/// A Merkle proof that opens several leaves at once, with the minimal set of nodes.
+///
+/// Going from the leaves to the root level by level, the sibling of each known node is either known as well
+/// (i.e. it is an opened leaf or it is computed from them) or it is provided within `nodes`. The nodes are
+/// in the order that they are needed, that is level by level and from left to right within a level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleMultiProof<T> {
+    pub nodes: Vec<T>,
+}
+
+impl<T: PartialEq + Eq + Clone> MerkleMultiProof<T> {
+    /// Creates a proof for the leaves at `indices` of `tree`, which may be unsorted and contain duplicates.
+    pub fn new<B: IsMerkleTreeBackend<Node = T>>(tree: &MerkleTree<B>, indices: &[usize]) -> Self {
+        // a single path for each leaf is enough to find the siblings on its way to the root
+        let paths = indices
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|&idx| {
+                let path = tree.get_proof_by_pos(idx).expect("index out-of-range");
+                (idx, path.merkle_path)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let depth = paths.values().next().map_or(0, |path| path.len());
+
+        // known nodes at the current level, mapped to the rest of the path of one of the leaves below them
+        let mut level = paths
+            .iter()
+            .map(|(&idx, path)| (idx, path.as_slice()))
+            .collect::<BTreeMap<_, _>>();
+        let mut nodes = Vec::new();
+        for _ in 0..depth {
+            for (&i, path) in &level {
+                if !level.contains_key(&(i ^ 1)) {
+                    nodes.push(path[0].clone());
+                }
+            }
+            level = level
+                .into_iter()
+                .map(|(i, path)| (i >> 1, &path[1..]))
+                .collect();
+        }
+
+        Self { nodes }
+    }
+
+    /// Verifies that `leaves` are at the given indices within the tree of `num_leaves` leaves with the given `root`.
+    ///
+    /// The leaves may be unsorted, and the same index may be opened more than once as long as the data is the same.
+    pub fn verify<B: IsMerkleTreeBackend<Node = T>>(
+        &self,
+        root: &T,
+        num_leaves: usize,
+        leaves: &[(usize, &B::Data)],
+    ) -> bool {
+        let mut level = BTreeMap::new();
+        for (idx, data) in leaves {
+            if *idx >= num_leaves {
+                return false;
+            }
+            let node = B::hash_data(data);
+            if level
+                .insert(*idx, node.clone())
+                .is_some_and(|other| other != node)
+            {
+                return false;
+            }
+        }
+
+        let depth = num_leaves.next_power_of_two().trailing_zeros();
+        let mut nodes = self.nodes.iter();
+        for _ in 0..depth {
+            let mut next = BTreeMap::new();
+            for (&i, node) in &level {
+                if i % 2 == 1 && level.contains_key(&(i - 1)) {
+                    continue; // already hashed together with its left sibling
+                }
+                let sibling = match level.get(&(i ^ 1)) {
+                    Some(sibling) => sibling,
+                    None => match nodes.next() {
+                        Some(sibling) => sibling,
+                        None => return false,
+                    },
+                };
+                let parent = if i % 2 == 0 {
+                    B::hash_new_parent(node, sibling)
+                } else {
+                    B::hash_new_parent(sibling, node)
+                };
+                next.insert(i >> 1, parent);
+            }
+            level = next;
+        }
+
+        nodes.next().is_none() && level.len() == 1 && level.get(&0) == Some(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{
+        Stark101PrimeField as F, Stark101PrimeFieldBackend as B, Stark101PrimeFieldElement as FE,
+    };
+
+    fn make_tree() -> (Vec<Vec<FE>>, MerkleTree<B>) {
+        let leaves = (0..16u64)
+            .map(|i| vec![FE::from(i), FE::from(i * i)])
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<B>::build(&leaves);
+        (leaves, tree)
+    }
+
+    fn open(leaves: &[Vec<FE>], indices: &[usize]) -> Vec<(usize, Vec<FE>)> {
+        indices.iter().map(|&i| (i, leaves[i].clone())).collect()
+    }
+
+    fn verify(
+        proof: &MerkleMultiProof<[u8; 32]>,
+        root: &[u8; 32],
+        opened: &[(usize, Vec<FE>)],
+    ) -> bool {
+        let opened = opened
+            .iter()
+            .map(|(i, data)| (*i, data))
+            .collect::<Vec<_>>();
+        proof.verify::<B>(root, 16, &opened)
+    }
+
+    #[test]
+    fn test_multi_proof() {
+        let (leaves, tree) = make_tree();
+        for indices in [vec![3], vec![0, 1], vec![9, 2, 15, 9], (0..16).collect()] {
+            let proof = MerkleMultiProof::new(&tree, &indices);
+            assert!(verify(&proof, &tree.root, &open(&leaves, &indices)));
+        }
+
+        // a single leaf needs the entire path, siblings need one node less, and all leaves need none
+        assert_eq!(MerkleMultiProof::new(&tree, &[3]).nodes.len(), 4);
+        assert_eq!(MerkleMultiProof::new(&tree, &[0, 1]).nodes.len(), 3);
+        assert_eq!(
+            MerkleMultiProof::new(&tree, &(0..16).collect::<Vec<_>>())
+                .nodes
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_leaf_salts() {
+        let leaves = vec![vec![FE::from(1u64)], vec![FE::from(2u64)]];
+        assert_eq!(LeafSalts::new(false).apply(0, &leaves), leaves);
+
+        let salts = LeafSalts::new(true);
+        let salted = salts.apply(0, &leaves);
+        assert_eq!(salted[1].len(), 1 + SALT_LENGTH);
+        assert_eq!(salted[1][1..], salts.salt::<F>(0, 1));
+        assert_ne!(salts.salt::<F>(0, 1), salts.salt::<F>(1, 1));
+        assert_ne!(
+            salts.apply(0, &leaves),
+            LeafSalts::new(true).apply(0, &leaves)
+        );
+    }
+
+    #[test]
+    fn test_multi_proof_invalid() {
+        let (leaves, tree) = make_tree();
+        let indices = [2, 9, 15];
+        let proof = MerkleMultiProof::new(&tree, &indices);
+
+        // wrong leaf data
+        let mut opened = open(&leaves, &indices);
+        opened[1].1[0] = FE::from(42u64);
+        assert!(!verify(&proof, &tree.root, &opened));
+
+        // conflicting data for the same index
+        let mut opened = open(&leaves, &indices);
+        opened.push((9, leaves[8].clone()));
+        assert!(!verify(&proof, &tree.root, &opened));
+
+        // leaf opened at a different index
+        let mut opened = open(&leaves, &indices);
+        opened[0].0 = 3;
+        assert!(!verify(&proof, &tree.root, &opened));
+
+        // extra nodes
+        let mut extra = proof.clone();
+        extra.nodes.push(tree.root);
+        assert!(!verify(&extra, &tree.root, &open(&leaves, &indices)));
+    }
+}
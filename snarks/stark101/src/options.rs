@@ -0,0 +1,196 @@
+/// Protocol options, known to both the prover and the verifier.
+///
+/// - `blowup_factor`: how many times larger the evaluation domain is compared to the trace domain.
+/// - `num_queries`: number of FRI queries to decommit on.
+/// - `grinding_bits`: number of leading zero bits required from the proof-of-work before the queries are sampled.
+/// - `fri_folding_factor`: how many times smaller each FRI layer is compared to the previous one.
+/// - `zero_knowledge`: whether the proof should hide the trace, see below.
+///
+/// Larger blowup and more queries increase the security at the cost of a larger proof (and prover time for the former),
+/// while a larger folding factor results in fewer FRI layers but more evaluations per layer.
+///
+/// In zero-knowledge mode, the trace polynomials are randomized and the composition polynomial is masked, which
+/// doubles the degree bound of the composition polynomial; that is, half of the blowup is spent on hiding.
+#[derive(Debug, Clone)]
+pub struct StarkOptions {
+    pub blowup_factor: usize,
+    pub num_queries: usize,
+    pub grinding_bits: usize,
+    pub fri_folding_factor: usize,
+    pub zero_knowledge: bool,
+}
+
+impl Default for StarkOptions {
+    /// The options from the Stark101 tutorial.
+    fn default() -> Self {
+        Self {
+            blowup_factor: 8,
+            num_queries: 3,
+            grinding_bits: 0,
+            fri_folding_factor: 2,
+            zero_knowledge: false,
+        }
+    }
+}
+
+impl StarkOptions {
+    /// Checks that the options are usable with a trace domain of size `n + 1`.
+    ///
+    /// ## Panics
+    ///
+    /// If any of the options are invalid.
+    pub fn validate(&self, n: usize) {
+        assert_eq!((n + 1).count_ones(), 1, "n + 1 must be a power of 2");
+        assert_eq!(
+            self.blowup_factor.count_ones(),
+            1,
+            "blowup must be a power of 2"
+        );
+        assert!(self.blowup_factor > 1, "blowup must be larger than 1");
+        assert!(self.num_queries > 0, "there must be at least one query");
+        assert!(self.grinding_bits <= 32, "grinding bits must not exceed 32");
+        assert_eq!(
+            self.fri_folding_factor.count_ones(),
+            1,
+            "folding factor must be a power of 2"
+        );
+        assert!(
+            self.fri_folding_factor > 1,
+            "folding factor must be larger than 1"
+        );
+        // the last FRI layer may be smaller than the blowup factor, but it must not vanish
+        assert!(
+            self.fri_folding_factor <= self.effective_blowup_factor(),
+            "folding factor must not exceed the blowup factor"
+        );
+    }
+
+    /// Degree bound of the composition polynomial for a trace domain of size `n + 1`, which is `n + 1` unless
+    /// zero-knowledge is enabled, in which case it is doubled.
+    pub fn cp_degree_bound(&self, n: usize) -> usize {
+        if self.zero_knowledge {
+            2 * (n + 1)
+        } else {
+            n + 1
+        }
+    }
+
+    /// Ratio of the evaluation domain size to the degree bound of the composition polynomial.
+    fn effective_blowup_factor(&self) -> usize {
+        if self.zero_knowledge {
+            self.blowup_factor / 2
+        } else {
+            self.blowup_factor
+        }
+    }
+
+    /// Number of FRI folds that take the composition polynomial down to a constant, see `cp_degree_bound`.
+    pub fn num_fri_folds(&self, n: usize) -> usize {
+        let log_degree = self.cp_degree_bound(n).trailing_zeros() as usize;
+        let log_folding = self.fri_folding_factor.trailing_zeros() as usize;
+        log_degree.div_ceil(log_folding)
+    }
+
+    /// Conjectured bits of security of a proof with these options, for a trace domain of size `n + 1`,
+    /// challenges sampled from a field of `field_bits` bits (see `extension::field_bits`), and Merkle commitments
+    /// with `collision_bits` bits of collision resistance (see `MerkleBackend::COLLISION_BITS`).
+    ///
+    /// Following the ethSTARK conjecture, each query contributes `log2(blowup_factor)` bits (one less in
+    /// zero-knowledge mode) on top of the grinding bits, and the result is capped by the bits we get from sampling the challenges over the field
+    /// (minus the size of the evaluation domain) and the collision resistance of the hash function.
+    ///
+    /// When the challenges are sampled from the tiny Stark101 field itself the field part dominates, so an extension
+    /// field is needed to go beyond that; this is useful for trading off proof size against security, not as a
+    /// production estimate.
+    pub fn conjectured_security_bits(
+        &self,
+        n: usize,
+        field_bits: usize,
+        collision_bits: usize,
+    ) -> usize {
+        let log_blowup = self.effective_blowup_factor().trailing_zeros() as usize;
+        let query_bits = self.num_queries * log_blowup + self.grinding_bits;
+
+        let log_domain = ((n + 1) * self.blowup_factor).trailing_zeros() as usize;
+        let field_security = field_bits.saturating_sub(log_domain);
+
+        query_bits.min(field_security).min(collision_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        field::Stark101PrimeField,
+        hash::{MerkleBackend, Sha256Backend},
+        poseidon::PoseidonBackend,
+    };
+
+    #[test]
+    fn test_num_fri_folds() {
+        let options = StarkOptions::default();
+        assert_eq!(options.num_fri_folds(1023), 10);
+
+        let options = StarkOptions {
+            fri_folding_factor: 8,
+            ..StarkOptions::default()
+        };
+        assert_eq!(options.num_fri_folds(1023), 4);
+
+        let options = StarkOptions {
+            zero_knowledge: true,
+            ..StarkOptions::default()
+        };
+        assert_eq!(options.num_fri_folds(1023), 11);
+    }
+
+    #[test]
+    fn test_security_bits() {
+        // 3 queries with blowup 8 give 9 bits, the tutorial is not meant to be secure
+        let options = StarkOptions::default();
+        assert_eq!(options.conjectured_security_bits(1023, 31, 128), 9);
+
+        // more queries are capped by the field: log2(p) = 31, and the domain has 2^13 elements
+        let options = StarkOptions {
+            num_queries: 30,
+            ..StarkOptions::default()
+        };
+        assert_eq!(options.conjectured_security_bits(1023, 31, 128), 31 - 13);
+
+        // which is twice as large when the challenges are from the quadratic extension
+        assert_eq!(options.conjectured_security_bits(1023, 62, 128), 62 - 13);
+
+        // grinding adds its bits on top of the queries
+        let options = StarkOptions {
+            grinding_bits: 8,
+            ..StarkOptions::default()
+        };
+        assert_eq!(options.conjectured_security_bits(1023, 31, 128), 9 + 8);
+
+        // zero-knowledge spends half of the blowup
+        let options = StarkOptions {
+            zero_knowledge: true,
+            ..StarkOptions::default()
+        };
+        assert_eq!(options.conjectured_security_bits(1023, 31, 128), 6);
+
+        // with challenges from the quartic extension, the Poseidon digests cap the security at their collision
+        // resistance, whereas the 256-bit ones leave it to the queries
+        let options = StarkOptions {
+            num_queries: 36,
+            ..StarkOptions::default()
+        };
+        let poseidon_bits = <PoseidonBackend as MerkleBackend<Stark101PrimeField>>::COLLISION_BITS;
+        let sha256_bits = <Sha256Backend<Stark101PrimeField> as MerkleBackend<_>>::COLLISION_BITS;
+        assert_eq!((poseidon_bits, sha256_bits), (62, 128));
+        assert_eq!(
+            options.conjectured_security_bits(1023, 124, poseidon_bits),
+            62
+        );
+        assert_eq!(
+            options.conjectured_security_bits(1023, 124, sha256_bits),
+            36 * 3
+        );
+    }
+}
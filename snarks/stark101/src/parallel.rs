@@ -0,0 +1,94 @@
+//! Iterators that are parallel with the `parallel` feature, and serial otherwise.
+//!
+//! The prover uses these for its loops over the evaluation domain, so that the same code runs either way; files
+//! that use them import `rayon::prelude::*` with the feature as well. Each loop computes every element on its own,
+//! without any state shared across elements, so the proofs are the same with or without the feature.
+
+use lambdaworks_math::field::{element::FieldElement, errors::FieldError, traits::IsField};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Iterates over the elements of `$e` by reference, i.e. `par_iter` or `iter`.
+macro_rules! iter {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let it = $e.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let it = $e.iter();
+        it
+    }};
+}
+
+/// Iterates over the elements of `$e` by mutable reference, i.e. `par_iter_mut` or `iter_mut`.
+macro_rules! iter_mut {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let it = $e.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let it = $e.iter_mut();
+        it
+    }};
+}
+
+/// Iterates over the elements of `$e` by value, e.g. a vector or a range, i.e. `into_par_iter` or `into_iter`.
+macro_rules! into_iter {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let it = $e.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        #[allow(clippy::useless_conversion)]
+        let it = IntoIterator::into_iter($e);
+        it
+    }};
+}
+
+/// Iterates over the chunks of `$e` of size `$size` by mutable reference, i.e. `par_chunks_mut` or `chunks_mut`.
+macro_rules! chunks_mut {
+    ($e:expr, $size:expr) => {{
+        #[cfg(feature = "parallel")]
+        let it = $e.par_chunks_mut($size);
+        #[cfg(not(feature = "parallel"))]
+        let it = $e.chunks_mut($size);
+        it
+    }};
+}
+
+pub(crate) use {chunks_mut, into_iter, iter, iter_mut};
+
+/// Inverts all elements of `values` in place, same as `FieldElement::inplace_batch_inverse`.
+///
+/// With the `parallel` feature, the values are split into a chunk per thread and each chunk is inverted in batch,
+/// which costs an inversion per chunk instead of a single one overall.
+pub fn batch_inverse<F: IsField<BaseType: Send + Sync>>(
+    values: &mut [FieldElement<F>],
+) -> Result<(), FieldError> {
+    #[cfg(feature = "parallel")]
+    {
+        let chunk_size = values.len().div_ceil(rayon::current_num_threads()).max(1);
+        values
+            .par_chunks_mut(chunk_size)
+            .try_for_each(FieldElement::inplace_batch_inverse)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        FieldElement::inplace_batch_inverse(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Stark101PrimeFieldElement as FE;
+
+    #[test]
+    fn test_batch_inverse() {
+        let mut values = (1..=100u64).map(FE::from).collect::<Vec<_>>();
+        batch_inverse(&mut values).unwrap();
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(value * FE::from(i as u64 + 1), FE::one());
+        }
+
+        values.push(FE::zero());
+        assert!(batch_inverse(&mut values).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use std::sync::OnceLock;
+
+use lambdaworks_crypto::{
+    fiat_shamir::is_transcript::IsTranscript, merkle_tree::traits::IsMerkleTreeBackend,
+};
+use lambdaworks_math::field::element::FieldElement;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    field::{Stark101PrimeField, Stark101PrimeFieldElement as FE},
+    hash::MerkleNode,
+};
+
+/// Number of field elements in the Poseidon state.
+pub const WIDTH: usize = 8;
+
+/// Number of field elements absorbed per permutation, the rest of the state is the capacity.
+pub const RATE: usize = 4;
+
+/// Number of field elements in a digest, so that a digest has `4 . floor(log2(p)) = 124` bits, and thus only
+/// 62 bits of collision resistance by the birthday bound.
+pub const DIGEST_LENGTH: usize = 4;
+
+/// Number of full rounds, half of them before the partial rounds and half after.
+///
+/// There is no published instance over our field, so the round numbers follow Section 5.5 of the paper for
+/// `M = 128` bits of security, with `t = WIDTH`, `alpha = ALPHA` and `log2(p) ~ 31.6`. The statistical attacks
+/// need `R_F >= 6`, and the interpolation attack needs `R_F + R_P >= 17`, which is tighter than the Gröbner
+/// basis bounds; `R_F = 6` and `R_P = 11` use the fewest S-boxes. The paper then adds 2 full rounds and 7.5%
+/// partial rounds as a security margin.
+const FULL_ROUNDS: usize = 8;
+
+/// Number of partial rounds, where the S-box is applied only to the first element of the state.
+///
+/// The margin of `FULL_ROUNDS` gives `R_P = 12`; we use 22 instead, since a partial round costs a single
+/// S-box and the bounds are less studied over such a small field.
+const PARTIAL_ROUNDS: usize = 22;
+
+/// Exponent of the S-box. LambdaWorks uses `x^3`, which is not a permutation over our field since `3` divides
+/// `p - 1 = 3 . 2^30`, so we use the smallest exponent coprime to `p - 1` instead.
+const ALPHA: u64 = 5;
+
+/// A Poseidon digest, i.e. the first `DIGEST_LENGTH` elements of the state.
+pub type PoseidonDigest = [FE; DIGEST_LENGTH];
+
+/// Poseidon permutation over the Stark101 field, see [the paper](https://eprint.iacr.org/2019/458).
+///
+/// The MDS matrix is the Cauchy matrix `M[i][j] = 1 / (i - (WIDTH + j))`, and the round constants are derived
+/// from SHA256 so that there is nothing up our sleeve.
+pub struct Poseidon {
+    round_constants: Vec<[FE; WIDTH]>,
+    mds: [[FE; WIDTH]; WIDTH],
+}
+
+impl Poseidon {
+    fn new() -> Self {
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|round| {
+                std::array::from_fn(|i| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(b"stark101-poseidon");
+                    hasher.update((round as u64).to_be_bytes());
+                    hasher.update((i as u64).to_be_bytes());
+                    let hash: [u8; 32] = hasher.finalize().into();
+                    FE::from(u64::from_be_bytes(hash[..8].try_into().unwrap()))
+                })
+            })
+            .collect();
+
+        let mds = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                (FE::from(i as u64) - FE::from((WIDTH + j) as u64))
+                    .inv()
+                    .expect("x_i and y_j are distinct")
+            })
+        });
+
+        Self {
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Returns the permutation with its constants computed once.
+    pub fn instance() -> &'static Self {
+        static INSTANCE: OnceLock<Poseidon> = OnceLock::new();
+        INSTANCE.get_or_init(Poseidon::new)
+    }
+
+    /// Applies the permutation to `state` in place.
+    pub fn permute(&self, state: &mut [FE; WIDTH]) {
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x += *c;
+            }
+
+            let half_full = FULL_ROUNDS / 2;
+            if round < half_full || round >= half_full + PARTIAL_ROUNDS {
+                for x in state.iter_mut() {
+                    *x = x.pow(ALPHA);
+                }
+            } else {
+                state[0] = state[0].pow(ALPHA);
+            }
+
+            *state = std::array::from_fn(|i| {
+                self.mds[i]
+                    .iter()
+                    .zip(state.iter())
+                    .fold(FE::zero(), |acc, (m, x)| acc + m * x)
+            });
+        }
+    }
+
+    /// Hashes any number of field elements with a sponge.
+    ///
+    /// The length of the input is written to the capacity, so that inputs of different lengths do not collide
+    /// even though the last chunk is padded with zeros.
+    pub fn hash(&self, input: &[FE]) -> PoseidonDigest {
+        let mut state = [FE::zero(); WIDTH];
+        state[RATE] = FE::from(input.len() as u64);
+        for chunk in input.chunks(RATE) {
+            for (x, y) in state.iter_mut().zip(chunk) {
+                *x += *y;
+            }
+            self.permute(&mut state);
+        }
+        if input.is_empty() {
+            self.permute(&mut state);
+        }
+
+        std::array::from_fn(|i| state[i])
+    }
+}
+
+/// Canonical 32-bit big-endian encoding of a field element, which fits since `p < 2^32`.
+fn element_to_bytes(element: &FE) -> [u8; 4] {
+    (element.representative().limbs[0] as u32).to_be_bytes()
+}
+
+/// Decodes an element encoded with `element_to_bytes`, returning `None` if it is not canonical.
+fn element_from_bytes(bytes: &[u8]) -> Option<FE> {
+    let value = u32::from_be_bytes(bytes.try_into().ok()?) as u64;
+    let element = FE::from(value);
+    (element.representative().limbs[0] == value).then_some(element)
+}
+
+impl MerkleNode for PoseidonDigest {
+    const NUM_BYTES: usize = 4 * DIGEST_LENGTH;
+    const COLLISION_BITS: usize = DIGEST_LENGTH * 31 / 2;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.iter().flat_map(element_to_bytes).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NUM_BYTES {
+            return None;
+        }
+        let elements = bytes
+            .chunks(4)
+            .map(element_from_bytes)
+            .collect::<Option<Vec<_>>>()?;
+        elements.try_into().ok()
+    }
+}
+
+/// Backend for Merkle Tree using Poseidon, where each leaf is a row of field elements.
+///
+/// Unlike the byte-oriented hashes, the nodes are field elements as well, which is what a recursive verifier
+/// within a STARK would want.
+#[derive(Clone, Default)]
+pub struct PoseidonBackend;
+
+impl IsMerkleTreeBackend for PoseidonBackend {
+    type Node = PoseidonDigest;
+    type Data = Vec<FE>;
+
+    fn hash_data(leaf: &Vec<FE>) -> PoseidonDigest {
+        Poseidon::instance().hash(leaf)
+    }
+
+    fn hash_new_parent(left: &PoseidonDigest, right: &PoseidonDigest) -> PoseidonDigest {
+        Poseidon::instance().hash(&[*left, *right].concat())
+    }
+}
+
+/// Transcript for Fiat-Shamir transform using a Poseidon duplex sponge.
+///
+/// Field elements are absorbed as they are, and bytes are absorbed three at a time along with their length,
+/// so that appending the roots of a byte-oriented Merkle tree still works. Pending elements are absorbed
+/// when a challenge is sampled.
+#[derive(Clone)]
+pub struct PoseidonTranscript {
+    state: [FE; WIDTH],
+    pending: Vec<FE>,
+}
+
+impl Default for PoseidonTranscript {
+    fn default() -> Self {
+        Self {
+            state: [FE::zero(); WIDTH],
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl PoseidonTranscript {
+    /// Absorbs the pending elements, and permutes once more so that consecutive samples differ.
+    fn squeeze(&mut self) -> FE {
+        let poseidon = Poseidon::instance();
+        for chunk in self.pending.chunks(RATE) {
+            for (x, y) in self.state.iter_mut().zip(chunk) {
+                *x += *y;
+            }
+            poseidon.permute(&mut self.state);
+        }
+        self.pending.clear();
+        poseidon.permute(&mut self.state);
+        self.state[0]
+    }
+}
+
+impl IsTranscript<Stark101PrimeField> for PoseidonTranscript {
+    fn append_field_element(&mut self, element: &FieldElement<Stark101PrimeField>) {
+        self.pending.push(*element);
+    }
+
+    fn append_bytes(&mut self, new_bytes: &[u8]) {
+        self.pending.push(FE::from(new_bytes.len() as u64));
+        for chunk in new_bytes.chunks(3) {
+            let mut limb = [0u8; 8];
+            limb[8 - chunk.len()..].copy_from_slice(chunk);
+            self.pending.push(FE::from(u64::from_be_bytes(limb)));
+        }
+    }
+
+    fn state(&self) -> [u8; 32] {
+        let mut transcript = self.clone();
+        transcript.squeeze();
+        let mut state = [0u8; 32];
+        for (bytes, x) in state.chunks_mut(4).zip(&transcript.state) {
+            bytes.copy_from_slice(&element_to_bytes(x));
+        }
+        state
+    }
+
+    fn sample_field_element(&mut self) -> FieldElement<Stark101PrimeField> {
+        self.squeeze()
+    }
+
+    fn sample_u64(&mut self, upper_bound: u64) -> u64 {
+        u64::from_be_bytes(self.state()[..8].try_into().unwrap()) % upper_bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lambdaworks_crypto::merkle_tree::merkle::MerkleTree;
+
+    #[test]
+    fn test_sbox_is_permutation() {
+        // x -> x^alpha is a bijection iff alpha is coprime to p - 1
+        let p_minus_one = 3 * (1u64 << 30);
+        assert_ne!(p_minus_one % ALPHA, 0);
+    }
+
+    #[test]
+    fn test_round_numbers() {
+        // bounds of Section 5.5 of the paper, without the security margin
+        let (m, t, log_p) = (
+            128.0,
+            WIDTH as f64,
+            (3.0 * (1u64 << 30) as f64 + 1.0).log2(),
+        );
+        let log_alpha = |x: f64| x.log2() / (ALPHA as f64).log2();
+        let r_f = (FULL_ROUNDS - 2) as f64;
+        let r_p = (PARTIAL_ROUNDS as f64 / 1.075).floor();
+
+        // statistical attacks
+        assert!(m <= (log_p.floor() - (ALPHA - 1) as f64 / 2.0) * (t + 1.0));
+        assert!(r_f >= 6.0);
+        // interpolation attack
+        assert!(
+            r_f + r_p >= 1.0 + (log_alpha(2.0) * m.min(log_p.ceil())).ceil() + log_alpha(t).ceil()
+        );
+        // Gröbner basis attacks
+        assert!(r_f + r_p >= log_alpha(2.0) * m.min(log_p));
+        assert!(r_f + r_p >= t - 1.0 + log_alpha(2.0) * (m / (t + 1.0)).min(log_p / 2.0));
+        assert!((t - 1.0) * r_f + r_p >= t - 2.0 + m / (2.0 * (ALPHA as f64).log2()));
+    }
+
+    /// Determinant by Gaussian elimination.
+    fn determinant(mut m: Vec<Vec<FE>>) -> FE {
+        let mut det = FE::one();
+        for i in 0..m.len() {
+            let Some(pivot) = (i..m.len()).find(|&k| m[k][i] != FE::zero()) else {
+                return FE::zero();
+            };
+            if pivot != i {
+                m.swap(i, pivot);
+                det = -det;
+            }
+            det *= m[i][i];
+            let inv = m[i][i].inv().unwrap();
+            let (top, bottom) = m.split_at_mut(i + 1);
+            for row in bottom {
+                let factor = row[i] * inv;
+                for (x, y) in row.iter_mut().zip(&top[i]).skip(i) {
+                    *x = *x - factor * y;
+                }
+            }
+        }
+        det
+    }
+
+    #[test]
+    fn test_mds() {
+        // a matrix is MDS iff all of its square submatrices are nonsingular
+        let mds = Poseidon::instance().mds;
+        let subsets = |size: u32| (0..1u32 << WIDTH).filter(move |s| s.count_ones() == size);
+        for size in 1..=WIDTH as u32 {
+            for rows in subsets(size) {
+                for cols in subsets(size) {
+                    let submatrix = (0..WIDTH)
+                        .filter(|i| rows >> i & 1 == 1)
+                        .map(|i| {
+                            (0..WIDTH)
+                                .filter(|j| cols >> j & 1 == 1)
+                                .map(|j| mds[i][j])
+                                .collect()
+                        })
+                        .collect();
+                    assert_ne!(
+                        determinant(submatrix),
+                        FE::zero(),
+                        "rows {rows:b}, cols {cols:b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        let poseidon = Poseidon::instance();
+        let input = (0..10u64).map(FE::from).collect::<Vec<_>>();
+        assert_eq!(poseidon.hash(&input), poseidon.hash(&input));
+        assert_ne!(poseidon.hash(&input), poseidon.hash(&input[..9]));
+
+        // zero padding is not ambiguous
+        let padded = [input.clone(), vec![FE::zero()]].concat();
+        assert_ne!(poseidon.hash(&input), poseidon.hash(&padded));
+        assert_ne!(poseidon.hash(&[]), poseidon.hash(&[FE::zero()]));
+    }
+
+    #[test]
+    fn test_node_bytes() {
+        let node = Poseidon::instance().hash(&[FE::from(42u64)]);
+        let bytes = node.to_bytes();
+        assert_eq!(bytes.len(), PoseidonDigest::NUM_BYTES);
+        assert_eq!(PoseidonDigest::from_bytes(&bytes), Some(node));
+
+        // p = 0xc0000001 is not canonical
+        let mut bytes = bytes;
+        bytes[..4].copy_from_slice(&0xc0000001u32.to_be_bytes());
+        assert_eq!(PoseidonDigest::from_bytes(&bytes), None);
+    }
+
+    #[test]
+    fn test_merkle_tree() {
+        let leaves = (0..8u64)
+            .map(|i| vec![FE::from(i), FE::from(i * i)])
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<PoseidonBackend>::build(&leaves);
+        let proof = tree.get_proof_by_pos(3).unwrap();
+        assert!(proof.verify::<PoseidonBackend>(&tree.root, 3, &leaves[3]));
+        assert!(!proof.verify::<PoseidonBackend>(&tree.root, 3, &leaves[4]));
+    }
+
+    #[test]
+    fn test_transcript() {
+        let mut transcript = PoseidonTranscript::default();
+        transcript.append_bytes(&[1, 2, 3, 4]);
+        transcript.append_field_element(&FE::from(5u64));
+
+        // sampling does not depend on the state being read, and consecutive samples differ
+        let state = transcript.state();
+        assert_eq!(state, transcript.state());
+        let a = transcript.sample_field_element();
+        let b = transcript.sample_field_element();
+        assert_ne!(a, b);
+        assert_ne!(state, transcript.state());
+
+        // bytes are absorbed along with their length
+        let mut other = PoseidonTranscript::default();
+        other.append_bytes(&[1, 2, 3, 4, 0]);
+        other.append_field_element(&FE::from(5u64));
+        assert_ne!(state, other.state());
+    }
+}
@@ -1,5 +1,7 @@
 use lambdaworks_math::field::{element::FieldElement, traits::IsField};
 
+use crate::air::{Air, AirExpr, BoundaryConstraint};
+
 /// Returns the trace of a program for `FibonacciSq`.
 pub fn fibonacci_square<F: IsField>(
     a_0: FieldElement<F>,
@@ -18,6 +20,217 @@ pub fn fibonacci_square<F: IsField>(
     trace
 }
 
+/// Returns the trace of a program for `Fibonacci`.
+pub fn fibonacci<F: IsField>(
+    a_0: FieldElement<F>,
+    a_1: FieldElement<F>,
+    n: usize,
+) -> Vec<FieldElement<F>> {
+    let mut trace = Vec::with_capacity(n);
+    trace.push(a_0);
+    trace.push(a_1);
+
+    for i in 2..n {
+        let a_i = trace[i - 1].clone() + trace[i - 2].clone();
+        trace.push(a_i);
+    }
+
+    trace
+}
+
+/// Returns the trace of a counter that starts at `a_0` and is incremented by `step` at each row.
+pub fn counter<F: IsField>(
+    a_0: FieldElement<F>,
+    step: FieldElement<F>,
+    n: usize,
+) -> Vec<FieldElement<F>> {
+    let mut trace = Vec::with_capacity(n);
+    trace.push(a_0);
+
+    for i in 1..n {
+        let a_i = trace[i - 1].clone() + step.clone();
+        trace.push(a_i);
+    }
+
+    trace
+}
+
+/// Returns the trace of a program for `Fibonacci` with two registers `(x, y)`, where at each step
+/// `(x, y) <- (y, x + y)`. The trace is returned as two columns, one for each register.
+pub fn fibonacci_registers<F: IsField>(
+    a_0: FieldElement<F>,
+    a_1: FieldElement<F>,
+    n: usize,
+) -> Vec<Vec<FieldElement<F>>> {
+    let mut xs = Vec::with_capacity(n);
+    let mut ys = Vec::with_capacity(n);
+    xs.push(a_0);
+    ys.push(a_1);
+
+    for i in 1..n {
+        let x_i = ys[i - 1].clone();
+        let y_i = xs[i - 1].clone() + ys[i - 1].clone();
+        xs.push(x_i);
+        ys.push(y_i);
+    }
+
+    vec![xs, ys]
+}
+
+/// AIR for `FibonacciSq`, i.e. `a_0` is given, `a_(n-1)` is given and `a_(i+2) = a_(i+1)^2 + a_i^2`.
+#[derive(Debug, Clone)]
+pub struct FibonacciSqAir<F: IsField> {
+    pub n: usize,
+    pub a_0: FieldElement<F>,
+    pub a_last: FieldElement<F>,
+}
+
+impl<F: IsField> Air<F> for FibonacciSqAir<F> {
+    fn trace_length(&self) -> usize {
+        self.n
+    }
+
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+        vec![
+            BoundaryConstraint {
+                row: 0,
+                column: 0,
+                value: self.a_0.clone(),
+            },
+            BoundaryConstraint {
+                row: self.n - 1,
+                column: 0,
+                value: self.a_last.clone(),
+            },
+        ]
+    }
+
+    fn transition_constraints(&self) -> Vec<AirExpr<F>> {
+        use AirExpr::Trace;
+
+        // f(g^2 . x) - f(g . x)^2 - f(x)^2
+        vec![Trace(0, 2) - Trace(0, 1) * Trace(0, 1) - Trace(0, 0) * Trace(0, 0)]
+    }
+}
+
+/// AIR for `Fibonacci`, i.e. `a_0` is given, `a_(n-1)` is given and `a_(i+2) = a_(i+1) + a_i`.
+#[derive(Debug, Clone)]
+pub struct FibonacciAir<F: IsField> {
+    pub n: usize,
+    pub a_0: FieldElement<F>,
+    pub a_last: FieldElement<F>,
+}
+
+impl<F: IsField> Air<F> for FibonacciAir<F> {
+    fn trace_length(&self) -> usize {
+        self.n
+    }
+
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+        vec![
+            BoundaryConstraint {
+                row: 0,
+                column: 0,
+                value: self.a_0.clone(),
+            },
+            BoundaryConstraint {
+                row: self.n - 1,
+                column: 0,
+                value: self.a_last.clone(),
+            },
+        ]
+    }
+
+    fn transition_constraints(&self) -> Vec<AirExpr<F>> {
+        use AirExpr::Trace;
+
+        // f(g^2 . x) - f(g . x) - f(x)
+        vec![Trace(0, 2) - Trace(0, 1) - Trace(0, 0)]
+    }
+}
+
+/// AIR for a counter, i.e. `a_0` is given, `a_(n-1)` is given and `a_(i+1) = a_i + step`.
+#[derive(Debug, Clone)]
+pub struct CounterAir<F: IsField> {
+    pub n: usize,
+    pub a_0: FieldElement<F>,
+    pub step: FieldElement<F>,
+    pub a_last: FieldElement<F>,
+}
+
+impl<F: IsField> Air<F> for CounterAir<F> {
+    fn trace_length(&self) -> usize {
+        self.n
+    }
+
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+        vec![
+            BoundaryConstraint {
+                row: 0,
+                column: 0,
+                value: self.a_0.clone(),
+            },
+            BoundaryConstraint {
+                row: self.n - 1,
+                column: 0,
+                value: self.a_last.clone(),
+            },
+        ]
+    }
+
+    fn transition_constraints(&self) -> Vec<AirExpr<F>> {
+        use AirExpr::{Constant, Trace};
+
+        // f(g . x) - f(x) - step
+        vec![Trace(0, 1) - Trace(0, 0) - Constant(self.step.clone())]
+    }
+}
+
+/// AIR for `Fibonacci` with two registers, i.e. `x_0 = a_0` is given, `y_(n-1) = a_last` is given and
+/// `x_(i+1) = y_i`, `y_(i+1) = x_i + y_i`.
+#[derive(Debug, Clone)]
+pub struct FibonacciRegistersAir<F: IsField> {
+    pub n: usize,
+    pub a_0: FieldElement<F>,
+    pub a_last: FieldElement<F>,
+}
+
+impl<F: IsField> Air<F> for FibonacciRegistersAir<F> {
+    fn trace_length(&self) -> usize {
+        self.n
+    }
+
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+        vec![
+            BoundaryConstraint {
+                row: 0,
+                column: 0,
+                value: self.a_0.clone(),
+            },
+            BoundaryConstraint {
+                row: self.n - 1,
+                column: 1,
+                value: self.a_last.clone(),
+            },
+        ]
+    }
+
+    fn transition_constraints(&self) -> Vec<AirExpr<F>> {
+        use AirExpr::Trace;
+
+        vec![
+            // x(g . x) - y(x)
+            Trace(0, 1) - Trace(1, 0),
+            // y(g . x) - x(x) - y(x)
+            Trace(1, 1) - Trace(0, 0) - Trace(1, 0),
+        ]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,4 +246,23 @@ mod tests {
         assert_eq!(a.len(), n);
         assert_eq!(*a.last().unwrap(), FE::from(2338775057u64));
     }
+
+    #[test]
+    fn test_fibonacci() {
+        let a = fibonacci(FE::from(1u64), FE::from(1u64), 10);
+        assert_eq!(a[9], FE::from(55u64));
+    }
+
+    #[test]
+    fn test_fibonacci_registers() {
+        let a = fibonacci_registers(FE::from(1u64), FE::from(1u64), 10);
+        assert_eq!(a.len(), 2);
+        assert_eq!(a[1][8], FE::from(55u64));
+    }
+
+    #[test]
+    fn test_counter() {
+        let a = counter(FE::from(5u64), FE::from(3u64), 10);
+        assert_eq!(a[9], FE::from(32u64));
+    }
 }
@@ -1,42 +1,574 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
 use lambdaworks_math::field::{element::FieldElement, traits::IsField};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    extension::ExtensionField,
+    hash::{FieldBytes, MerkleNode},
+    merkle::MerkleMultiProof,
+};
+
+/// Merkle roots of the trace, of the composition polynomial and of the FRI layers of the DEEP composition polynomial.
+///
+/// The roots are nodes of the Merkle tree backend that the proof was created with, i.e. 32 bytes for SHA256.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Stark101Commitment {
-    pub trace_root: [u8; 32],
-    pub cp_roots: Vec<[u8; 32]>,
+pub struct Stark101Commitment<N = [u8; 32]> {
+    pub trace_root: N,
+    pub composition_root: N,
+    pub cp_roots: Vec<N>,
 }
 
+/// Decommitment for a single query.
+///
+/// - `trace_row`: row of the trace at `x`, i.e. the evaluations of each trace column.
+/// - `cp_eval`: evaluation of the composition polynomial at `x`.
+/// - `evals`: evaluations of each committed FRI layer over the coset of the query index, e.g. at `x` and `-x`.
+/// - `salts`: in zero-knowledge mode, the salts of the opened leaves, i.e. of the trace row, the composition
+///   polynomial and then of each FRI coset; empty otherwise.
+///
+/// The trace and the salts are over the base field `F`, whereas the rest is over the extension field `E`.
+/// The Merkle proofs for these are batched over all queries within the proof.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Stark101Decommitment<F: IsField> {
-    pub evals: Vec<FieldElement<F>>,
-    pub paths: Vec<Vec<[u8; 32]>>,
+pub struct Stark101Decommitment<F: IsField, E: IsField = F> {
+    pub trace_row: Vec<FieldElement<F>>,
+    pub cp_eval: FieldElement<E>,
+    pub evals: Vec<FieldElement<E>>,
+    pub salts: Vec<Vec<FieldElement<F>>>,
 }
 
 /// A Stark101 proof, based on [this video](https://www.youtube.com/watch?v=CxP28qM4tAc) at 11:15.
 ///
 /// - `commitment`: the commitment to the proof, which includes the trace root and the composition polynomial roots.
+/// - `ood_frame`: rows of the trace at the out-of-domain point `z`, `g . z`, ... for each row offset within the window.
+/// - `ood_cp`: evaluation of the composition polynomial at `z`.
 /// - `decommitments`: the decommitments to the proof, which includes the evaluations of the trace and composition
-/// polynomial at the given index and their sibling along with Merkle authentication paths, for each query.
+///   polynomial at the given index and the FRI cosets, for each query.
+/// - `trace_proof`: Merkle multi-proof of the trace rows over all queries.
+/// - `cp_proof`: Merkle multi-proof of the composition polynomial evaluations over all queries.
+/// - `fri_proofs`: Merkle multi-proof of the opened cosets over all queries, for each committed FRI layer.
+/// - `fri_constant`: the constant polynomial at the last FRI layer, which the verifier needs before sampling queries.
+/// - `nonce`: proof-of-work nonce, found by grinding on the transcript state before sampling queries.
+/// - `h`: generator of the evaluation domain, such that the trace domain is generated by `g = h^blowup_factor`.
+/// - `w`: offset of the evaluation domain coset `w . H`.
+///
+/// The proof is generic over the extension field `E` that the challenges are sampled from, which is the base field
+/// `F` itself by default, and over the node type `N` of the Merkle tree backend, which is 32 bytes by default. The
+/// out-of-domain evaluations and `fri_constant` are over `E`, while `h` and `w` are over `F`.
+///
+/// Both `h` and `w` are derived from a fixed primitive root of the field, so they are the same for every proof
+/// with the same domain size; they are embedded in the proof so that the verifier can check them against
+/// the domain size without knowing how they were derived.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Stark101Proof<F: IsField> {
-    pub commitment: Stark101Commitment,
-    pub decommitments: Vec<Stark101Decommitment<F>>,
-}
-
-impl<F: IsField + Serialize> Stark101Proof<F> {
-    pub fn write_to_file(&self) -> String {
-        let proof_str = serde_json::to_string(&self).expect("should serialize");
-        let proof_filepath = std::env::current_dir().expect("should get current dir");
-        let proof_filepath = if proof_filepath.ends_with("lambda-0b10") {
-            proof_filepath.join("snarks/stark101/proof.json")
-        } else {
-            proof_filepath.join("proof.json")
+pub struct Stark101Proof<F: IsField, E: IsField = F, N = [u8; 32]> {
+    pub commitment: Stark101Commitment<N>,
+    pub ood_frame: Vec<Vec<FieldElement<E>>>,
+    pub ood_cp: FieldElement<E>,
+    pub decommitments: Vec<Stark101Decommitment<F, E>>,
+    pub trace_proof: MerkleMultiProof<N>,
+    pub cp_proof: MerkleMultiProof<N>,
+    pub fri_proofs: Vec<MerkleMultiProof<N>>,
+    pub fri_constant: FieldElement<E>,
+    pub nonce: u64,
+    pub h: FieldElement<F>,
+    pub w: FieldElement<F>,
+}
+
+/// Magic bytes at the start of a binary encoded proof.
+pub const PROOF_MAGIC: [u8; 4] = *b"S101";
+
+/// Version of the binary proof format.
+pub const PROOF_VERSION: u8 = 7;
+
+/// Size of an encoded field element in bytes, see `FieldBytes`.
+const ELEMENT_SIZE: usize = 8;
+
+/// Errors that may occur while encoding or decoding a proof.
+#[derive(Debug)]
+pub enum ProofError {
+    /// Reading or writing the proof failed.
+    Io(std::io::Error),
+    /// The bytes do not start with [`PROOF_MAGIC`].
+    InvalidMagic,
+    /// The proof was encoded with a different version of the format.
+    UnsupportedVersion(u8),
+    /// The proof was encoded with field elements of a different size.
+    FieldSizeMismatch(u8),
+    /// The proof was encoded with challenges from an extension of a different degree.
+    ExtensionDegreeMismatch(u8),
+    /// The proof was encoded with Merkle tree nodes of a different size.
+    NodeSizeMismatch(u8),
+    /// The bytes ended before the proof was fully decoded.
+    UnexpectedEnd,
+    /// There are bytes left after the proof was fully decoded.
+    TrailingBytes,
+    /// A field element is not in its canonical encoding.
+    InvalidFieldElement,
+    /// A Merkle tree node is not in its canonical encoding.
+    InvalidNode,
+    /// The proof has a collection with more than `u32::MAX` items.
+    TooLarge,
+}
+
+impl std::fmt::Display for ProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProofError::Io(e) => write!(f, "io error: {}", e),
+            ProofError::InvalidMagic => write!(f, "invalid magic bytes"),
+            ProofError::UnsupportedVersion(v) => write!(f, "unsupported version {}", v),
+            ProofError::FieldSizeMismatch(size) => write!(f, "unexpected field size {}", size),
+            ProofError::ExtensionDegreeMismatch(degree) => {
+                write!(f, "unexpected extension degree {}", degree)
+            }
+            ProofError::NodeSizeMismatch(size) => write!(f, "unexpected node size {}", size),
+            ProofError::UnexpectedEnd => write!(f, "unexpected end of bytes"),
+            ProofError::TrailingBytes => write!(f, "trailing bytes after proof"),
+            ProofError::InvalidFieldElement => write!(f, "invalid field element"),
+            ProofError::InvalidNode => write!(f, "invalid merkle node"),
+            ProofError::TooLarge => write!(f, "proof is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<std::io::Error> for ProofError {
+    fn from(e: std::io::Error) -> Self {
+        ProofError::Io(e)
+    }
+}
+
+/// Writes the proof in binary.
+struct ProofWriter {
+    bytes: Vec<u8>,
+}
+
+impl ProofWriter {
+    fn write_len(&mut self, len: usize) -> Result<(), ProofError> {
+        let len = u32::try_from(len).map_err(|_| ProofError::TooLarge)?;
+        self.bytes.extend_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_element<F: FieldBytes>(&mut self, element: &FieldElement<F>) {
+        self.bytes.extend_from_slice(&F::to_bytes(element));
+    }
+
+    fn write_elements<F: FieldBytes>(
+        &mut self,
+        elements: &[FieldElement<F>],
+    ) -> Result<(), ProofError> {
+        self.write_len(elements.len())?;
+        for element in elements {
+            self.write_element(element);
+        }
+        Ok(())
+    }
+
+    /// Writes an element of the extension as its coefficients over the base field, without a length.
+    fn write_extension_element<F: FieldBytes, E: ExtensionField<F>>(
+        &mut self,
+        element: &FieldElement<E>,
+    ) {
+        for coeff in E::to_base_coeffs(element) {
+            self.write_element(&coeff);
+        }
+    }
+
+    fn write_extension_elements<F: FieldBytes, E: ExtensionField<F>>(
+        &mut self,
+        elements: &[FieldElement<E>],
+    ) -> Result<(), ProofError> {
+        self.write_len(elements.len())?;
+        for element in elements {
+            self.write_extension_element::<F, E>(element);
+        }
+        Ok(())
+    }
+
+    fn write_node<N: MerkleNode>(&mut self, node: &N) {
+        self.bytes.extend_from_slice(&node.to_bytes());
+    }
+
+    fn write_nodes<N: MerkleNode>(&mut self, nodes: &[N]) -> Result<(), ProofError> {
+        self.write_len(nodes.len())?;
+        for node in nodes {
+            self.write_node(node);
+        }
+        Ok(())
+    }
+}
+
+/// Reads a binary proof, the counterpart of `ProofWriter`.
+struct ProofReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ProofReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ProofError> {
+        if self.bytes.len() < len {
+            return Err(ProofError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn read_len(&mut self) -> Result<usize, ProofError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    fn read_node<N: MerkleNode>(&mut self) -> Result<N, ProofError> {
+        N::from_bytes(self.read_bytes(N::NUM_BYTES)?).ok_or(ProofError::InvalidNode)
+    }
+
+    fn read_element<F: FieldBytes>(&mut self) -> Result<FieldElement<F>, ProofError> {
+        // reject non-canonical encodings, so that each proof has a single encoding
+        F::from_bytes(self.read_bytes(ELEMENT_SIZE)?).ok_or(ProofError::InvalidFieldElement)
+    }
+
+    fn read_elements<F: FieldBytes>(&mut self) -> Result<Vec<FieldElement<F>>, ProofError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_element()).collect()
+    }
+
+    fn read_extension_element<F: FieldBytes, E: ExtensionField<F>>(
+        &mut self,
+    ) -> Result<FieldElement<E>, ProofError> {
+        let coeffs = (0..E::DEGREE)
+            .map(|_| self.read_element())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(E::from_base_coeffs(&coeffs))
+    }
+
+    fn read_extension_elements<F: FieldBytes, E: ExtensionField<F>>(
+        &mut self,
+    ) -> Result<Vec<FieldElement<E>>, ProofError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_extension_element()).collect()
+    }
+
+    fn read_nodes<N: MerkleNode>(&mut self) -> Result<Vec<N>, ProofError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_node()).collect()
+    }
+}
+
+impl<F: FieldBytes, E: ExtensionField<F>, N: MerkleNode> Stark101Proof<F, E, N> {
+    /// Encodes the proof in binary.
+    ///
+    /// The proof starts with [`PROOF_MAGIC`], [`PROOF_VERSION`], the size of a field element in bytes, the degree
+    /// of the extension field and the size of a Merkle tree node in bytes, followed by:
+    ///
+    /// - the commitment roots
+    /// - the out-of-domain evaluations
+    /// - `fri_constant`, the proof-of-work `nonce`, `h` and `w`
+    /// - the nodes of the Merkle multi-proofs, for the trace, the composition polynomial and each FRI layer
+    /// - the decommitments, i.e. the opened trace row, composition polynomial, FRI cosets and salts of each query
+    ///
+    /// Field elements are written in their canonical big-endian bytes, with an element of the extension written as
+    /// its coefficients over the base field, lengths as little-endian `u32`s and the nonce as a little-endian `u64`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofError> {
+        let mut writer = ProofWriter { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(&PROOF_MAGIC);
+        writer.bytes.push(PROOF_VERSION);
+        writer.bytes.push(ELEMENT_SIZE as u8);
+        writer.bytes.push(E::DEGREE as u8);
+        writer.bytes.push(N::NUM_BYTES as u8);
+
+        writer.write_node(&self.commitment.trace_root);
+        writer.write_node(&self.commitment.composition_root);
+        writer.write_nodes(&self.commitment.cp_roots)?;
+        writer.write_len(self.ood_frame.len())?;
+        for row in &self.ood_frame {
+            writer.write_extension_elements::<F, E>(row)?;
+        }
+        writer.write_extension_element::<F, E>(&self.ood_cp);
+        writer.write_extension_element::<F, E>(&self.fri_constant);
+        writer.bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        writer.write_element(&self.h);
+        writer.write_element(&self.w);
+
+        writer.write_nodes(&self.trace_proof.nodes)?;
+        writer.write_nodes(&self.cp_proof.nodes)?;
+        writer.write_len(self.fri_proofs.len())?;
+        for fri_proof in &self.fri_proofs {
+            writer.write_nodes(&fri_proof.nodes)?;
+        }
+
+        writer.write_len(self.decommitments.len())?;
+        for decommitment in &self.decommitments {
+            writer.write_elements(&decommitment.trace_row)?;
+            writer.write_extension_element::<F, E>(&decommitment.cp_eval);
+            writer.write_extension_elements::<F, E>(&decommitment.evals)?;
+            writer.write_len(decommitment.salts.len())?;
+            for salt in &decommitment.salts {
+                writer.write_elements(salt)?;
+            }
+        }
+
+        Ok(writer.bytes)
+    }
+
+    /// Decodes a proof that was encoded with [`Stark101Proof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = ProofReader { bytes };
+
+        if reader.read_bytes(4)? != PROOF_MAGIC {
+            return Err(ProofError::InvalidMagic);
+        }
+        let version = reader.read_bytes(1)?[0];
+        if version != PROOF_VERSION {
+            return Err(ProofError::UnsupportedVersion(version));
+        }
+        let size = reader.read_bytes(1)?[0];
+        if size as usize != ELEMENT_SIZE {
+            return Err(ProofError::FieldSizeMismatch(size));
+        }
+        let degree = reader.read_bytes(1)?[0];
+        if degree as usize != E::DEGREE {
+            return Err(ProofError::ExtensionDegreeMismatch(degree));
+        }
+        let node_size = reader.read_bytes(1)?[0];
+        if node_size as usize != N::NUM_BYTES {
+            return Err(ProofError::NodeSizeMismatch(node_size));
+        }
+
+        let trace_root = reader.read_node()?;
+        let composition_root = reader.read_node()?;
+        let cp_roots = reader.read_nodes()?;
+        let num_ood_rows = reader.read_len()?;
+        let ood_frame = (0..num_ood_rows)
+            .map(|_| reader.read_extension_elements::<F, E>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let ood_cp = reader.read_extension_element::<F, E>()?;
+        let fri_constant = reader.read_extension_element::<F, E>()?;
+        let nonce = u64::from_le_bytes(reader.read_bytes(8)?.try_into().unwrap());
+        let h = reader.read_element()?;
+        let w = reader.read_element()?;
+
+        let trace_proof = MerkleMultiProof {
+            nodes: reader.read_nodes()?,
+        };
+        let cp_proof = MerkleMultiProof {
+            nodes: reader.read_nodes()?,
         };
+        let num_fri_proofs = reader.read_len()?;
+        let fri_proofs = (0..num_fri_proofs)
+            .map(|_| {
+                Ok(MerkleMultiProof {
+                    nodes: reader.read_nodes()?,
+                })
+            })
+            .collect::<Result<Vec<_>, ProofError>>()?;
+
+        let num_decommitments = reader.read_len()?;
+        let mut decommitments = Vec::new();
+        for _ in 0..num_decommitments {
+            let trace_row = reader.read_elements()?;
+            let cp_eval = reader.read_extension_element::<F, E>()?;
+            let evals = reader.read_extension_elements::<F, E>()?;
+            let num_salts = reader.read_len()?;
+            let salts = (0..num_salts)
+                .map(|_| reader.read_elements())
+                .collect::<Result<Vec<_>, _>>()?;
+            decommitments.push(Stark101Decommitment {
+                trace_row,
+                cp_eval,
+                evals,
+                salts,
+            });
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(ProofError::TrailingBytes);
+        }
+
+        Ok(Stark101Proof {
+            commitment: Stark101Commitment {
+                trace_root,
+                composition_root,
+                cp_roots,
+            },
+            ood_frame,
+            ood_cp,
+            decommitments,
+            trace_proof,
+            cp_proof,
+            fri_proofs,
+            fri_constant,
+            nonce,
+            h,
+            w,
+        })
+    }
+
+    /// Writes the binary encoding of the proof to `writer`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), ProofError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Reads a binary encoded proof from `reader`.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ProofError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Writes the binary encoding of the proof to the file at `path`.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), ProofError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads a binary encoded proof from the file at `path`.
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, ProofError> {
+        Self::read_from(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        field::{
+            Stark101PrimeField, Stark101PrimeFieldBackend, Stark101PrimeFieldElement as FE,
+            Stark101PrimeFieldExtension, Stark101PrimeFieldTranscript,
+        },
+        options::StarkOptions,
+        poseidon::{PoseidonBackend, PoseidonDigest, PoseidonTranscript},
+        program::{fibonacci_square, FibonacciSqAir},
+        prover::Stark101Prover,
+    };
+
+    fn make_proof() -> Stark101Proof<Stark101PrimeField> {
+        let (n, a_0, a_1) = (127, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        Stark101Prover::prove(&air, &trace, &StarkOptions::default())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let proof = make_proof();
+        let bytes = proof.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], &PROOF_MAGIC);
+
+        let decoded = Stark101Proof::<Stark101PrimeField>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_string(&proof).unwrap(),
+            serde_json::to_string(&decoded).unwrap()
+        );
+
+        let mut buf = Vec::new();
+        proof.write_to(&mut buf).unwrap();
+        let decoded = Stark101Proof::<Stark101PrimeField>::read_from(buf.as_slice()).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_size() {
+        let proof = make_proof();
+        let json = serde_json::to_string(&proof).unwrap();
+        let bytes = proof.to_bytes().unwrap();
+        log::debug!("JSON: {} bytes, binary: {} bytes", json.len(), bytes.len());
+        assert!(bytes.len() * 3 < json.len());
+    }
+
+    #[test]
+    fn test_round_trip_poseidon() {
+        let (n, a_0, a_1) = (127, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let proof = Stark101Prover::prove_with::<
+            Stark101PrimeField,
+            Stark101PrimeField,
+            PoseidonBackend,
+            PoseidonTranscript,
+            _,
+        >(&air, &trace, &StarkOptions::default());
+        let bytes = proof.to_bytes().unwrap();
+
+        let decoded =
+            Stark101Proof::<Stark101PrimeField, Stark101PrimeField, PoseidonDigest>::from_bytes(
+                &bytes,
+            )
+            .unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+
+        // the node size is checked before anything else
+        assert!(matches!(
+            Stark101Proof::<Stark101PrimeField>::from_bytes(&bytes),
+            Err(ProofError::NodeSizeMismatch(16))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_extension() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let proof = Stark101Prover::prove_with::<
+            Stark101PrimeField,
+            Stark101PrimeFieldExtension,
+            Stark101PrimeFieldBackend,
+            Stark101PrimeFieldTranscript,
+            _,
+        >(&air, &trace, &StarkOptions::default());
+        let bytes = proof.to_bytes().unwrap();
+
+        let decoded =
+            Stark101Proof::<Stark101PrimeField, Stark101PrimeFieldExtension>::from_bytes(&bytes)
+                .unwrap();
+        assert_eq!(decoded.ood_cp, proof.ood_cp);
+        assert_eq!(decoded.fri_constant, proof.fri_constant);
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+
+        // the extension degree is checked before the node size
+        assert!(matches!(
+            Stark101Proof::<Stark101PrimeField>::from_bytes(&bytes),
+            Err(ProofError::ExtensionDegreeMismatch(2))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let mut bytes = make_proof().to_bytes().unwrap();
+        let from_bytes = Stark101Proof::<Stark101PrimeField>::from_bytes;
+
+        assert!(matches!(
+            from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::UnexpectedEnd)
+        ));
+
+        bytes.push(0);
+        assert!(matches!(from_bytes(&bytes), Err(ProofError::TrailingBytes)));
+        bytes.pop();
 
-        let path_str = proof_filepath.to_str().unwrap().to_string();
-        std::fs::write(proof_filepath, proof_str).expect("unable to write");
+        bytes[4] = PROOF_VERSION + 1;
+        assert!(matches!(
+            from_bytes(&bytes),
+            Err(ProofError::UnsupportedVersion(_))
+        ));
 
-        path_str
+        bytes[0] = b'X';
+        assert!(matches!(from_bytes(&bytes), Err(ProofError::InvalidMagic)));
     }
 }
@@ -0,0 +1,926 @@
+#![allow(non_snake_case)]
+
+use lambdaworks_crypto::{
+    fiat_shamir::is_transcript::IsTranscript, merkle_tree::merkle::MerkleTree,
+};
+use lambdaworks_math::{field::element::FieldElement, polynomial::Polynomial};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    air::Air,
+    deep::{deep_composition_evals, sample_ood_point},
+    extension::{
+        append_extension_element, evaluate_at, field_bits, sample_extension_element, ExtensionField,
+    },
+    fft::{evaluate_offset_fft, interpolate_fft, interpolate_offset_fft, StarkField},
+    fri::{decommit_fri, fri_commit_evals, fri_layer_commit, COMPOSITION_TREE, TRACE_TREE},
+    grinding::grind,
+    hash::{Keccak256Transcript, MerkleBackend, MerkleNode, Sha256Backend},
+    merkle::LeafSalts,
+    options::StarkOptions,
+    parallel::{batch_inverse, into_iter, iter, iter_mut},
+    proof::{Stark101Commitment, Stark101Proof},
+};
+
+/// Interpolates a trace column of `n` values over the first `n` elements of the trace domain `<g>` of size `n + 1`.
+///
+/// The FFT needs a value for the last element as well, and we pick the one that makes the coefficient of `x^n`
+/// zero, so that we get the same polynomial of degree less than `n` as interpolating over `n` points would give.
+/// That coefficient is `(1 / (n + 1)) . sum_i v_i . g^(-i . n)`, and since `g^(-n) = g` it vanishes
+/// when `v_n = -g . sum_(i < n) v_i . g^i`.
+fn interpolate_column<F: StarkField>(
+    column: &[FieldElement<F>],
+    g: &FieldElement<F>,
+) -> Polynomial<FieldElement<F>> {
+    let mut g_pow = FieldElement::<F>::one();
+    let mut sum = FieldElement::<F>::zero();
+    for v in column {
+        sum += v * &g_pow;
+        g_pow *= g;
+    }
+
+    let mut values = column.to_vec();
+    values.push(-(g * sum));
+    interpolate_fft(&values, g)
+}
+
+/// Returns a polynomial with `num_coeffs` random coefficients.
+fn random_polynomial<F: StarkField>(num_coeffs: usize) -> Polynomial<FieldElement<F>> {
+    let coeffs = (0..num_coeffs)
+        .map(|_| FieldElement::<F>::from(rand::random::<u64>()))
+        .collect::<Vec<_>>();
+    Polynomial::new(&coeffs)
+}
+
+/// Randomizes the trace polynomial `f` as `f(x) + Z(x) . r(x)` where `Z(x) = x^(n+1) - 1` vanishes over the
+/// trace domain of size `n + 1` and `r` is a random polynomial with `num_coeffs` coefficients.
+///
+/// The result agrees with `f` over the trace domain, so the constraints still hold, while any `num_coeffs`
+/// evaluations outside of the trace domain are uniformly random.
+fn randomize_trace_polynomial<F: StarkField>(
+    f: &Polynomial<FieldElement<F>>,
+    n: usize,
+    num_coeffs: usize,
+) -> Polynomial<FieldElement<F>> {
+    let mut coeffs = f.coefficients.clone();
+    coeffs.resize(n + 1 + num_coeffs, FieldElement::zero());
+    for (i, r_i) in random_polynomial::<F>(num_coeffs)
+        .coefficients
+        .iter()
+        .enumerate()
+    {
+        coeffs[i] = &coeffs[i] - r_i;
+        coeffs[i + n + 1] += r_i.clone();
+    }
+    Polynomial::new(&coeffs)
+}
+
+/// Stark101 prover, for any program described by an [`Air`].
+pub struct Stark101Prover;
+
+impl Stark101Prover {
+    /// Proves that `trace` is a valid execution trace of the program described by `air`,
+    /// following the parts of the Stark101 tutorial. The trace is given as a list of columns.
+    ///
+    /// The composition polynomial is built from the constraints of `air`: each boundary constraint
+    /// `f_j(g^row) = value` gives `(f_j(x) - value) / (x - g^row)`, and each transition constraint `C` gives
+    /// `C(f_0(x), f_0(g . x), ..., f_1(x), ...) / Z(x)` where `Z` vanishes over the rows that the constraint
+    /// applies to.
+    ///
+    /// In zero-knowledge mode, the trace polynomials are randomized by `randomize_trace_polynomial`, random
+    /// masking polynomials are committed as additional trace columns and added to the composition polynomial,
+    /// one for each of its base field coefficients, and the Merkle leaves are salted.
+    ///
+    /// The proof is over any [`StarkField`], e.g. the Stark101 field, Goldilocks or BabyBear. The challenges are
+    /// sampled from the field itself, the Merkle trees use SHA256 and the transcript uses Keccak256, see
+    /// `prove_with` for other choices.
+    pub fn prove<F: StarkField, A: Air<F>>(
+        air: &A,
+        trace: &[Vec<FieldElement<F>>],
+        options: &StarkOptions,
+    ) -> Stark101Proof<F> {
+        Self::prove_with::<F, F, Sha256Backend<F>, Keccak256Transcript<F>, A>(air, trace, options)
+    }
+
+    /// Same as `prove`, but with the extension field `E` to sample the challenges from, the Merkle tree backend
+    /// `B` and the transcript `T` of choice, e.g. Keccak256, Blake3 or Poseidon (see `field.rs`). The verifier
+    /// must use the same ones.
+    ///
+    /// The trace is committed over the base field `F`, whereas the composition polynomial, the out-of-domain
+    /// evaluations and the FRI layers are over `E`, whose elements are committed as their coefficients over `F`.
+    ///
+    /// With the `parallel` feature, the loops over the trace columns and over the evaluation domain run on
+    /// multiple threads. The randomness is sampled in the same order either way, so the proof is the same.
+    pub fn prove_with<F, E, B, T, A>(
+        air: &A,
+        trace: &[Vec<FieldElement<F>>],
+        options: &StarkOptions,
+    ) -> Stark101Proof<F, E, B::Node>
+    where
+        F: StarkField,
+        E: ExtensionField<F>,
+        B: MerkleBackend<F>,
+        T: IsTranscript<F> + Default,
+        A: Air<F>,
+    {
+        let blowup_factor = options.blowup_factor;
+        let n = air.trace_length();
+        let window = air.window();
+        assert!(n >= window, "trace must be at least as long as the window");
+        options.validate(n);
+        log::debug!(
+            "Conjectured security is {} bits",
+            options.conjectured_security_bits(n, field_bits::<F, E>(), B::COLLISION_BITS)
+        );
+        assert_eq!(trace.len(), air.width(), "trace width mismatch");
+        for column in trace {
+            assert_eq!(column.len(), n, "trace length mismatch");
+        }
+
+        /////////////////////////////////////////////////////////////////////////////////
+        ///////////////////////////////////  PART 1  ////////////////////////////////////
+        /////////////////////////////////////////////////////////////////////////////////
+        log::info!("Checking that the trace satisfies the constraints");
+        let boundary_constraints = air.boundary_constraints();
+        let transition_constraints = air.transition_constraints();
+        for bc in &boundary_constraints {
+            assert_eq!(
+                trace[bc.column][bc.row], bc.value,
+                "boundary constraint not satisfied"
+            );
+        }
+        for i in 0..=n - window {
+            let frame = (i..i + window)
+                .map(|row| trace.iter().map(|column| column[row].clone()).collect())
+                .collect::<Vec<Vec<_>>>();
+            for tc in &transition_constraints {
+                let value = tc.evaluate(&frame, &|c| c.clone());
+                assert_eq!(
+                    value,
+                    FieldElement::zero(),
+                    "transition constraint not satisfied"
+                );
+            }
+        }
+
+        log::info!("Creating transcript");
+        let mut channel = T::default();
+
+        log::info!("Creating evaluation domain");
+        let G_order = n + 1;
+        let H_order = G_order * blowup_factor; // extend to a larger domain
+        let h = F::primitive_root_of_unity(H_order.trailing_zeros() as u64);
+        let g = h.pow(blowup_factor); // so that g . x is blowup_factor steps away from x within H
+        log::debug!("Trace domain has {} elements", G_order);
+
+        log::info!("Interpolating the trace columns");
+        let f = iter!(trace)
+            .map(|column| interpolate_column(column, &g))
+            .collect::<Vec<_>>();
+        log::debug!("Trace polynomials have degree {}", f[0].degree());
+
+        // each query opens a single evaluation of the trace polynomials and the out-of-domain frame opens `window`
+        // more over the extension, each of which is `E::DEGREE` evaluations over the base field, so that many
+        // random coefficients hide them; the composition polynomial is then masked in every coordinate by random
+        // polynomials of its degree bound
+        let salts = LeafSalts::new(options.zero_knowledge);
+        let f = if options.zero_knowledge {
+            log::info!("Randomizing the trace polynomials");
+            let num_coeffs = options.num_queries + window * E::DEGREE;
+            assert!(2 * num_coeffs <= n, "too many queries for zero-knowledge");
+            let mut f = f
+                .iter()
+                .map(|f_j| randomize_trace_polynomial(f_j, n, num_coeffs))
+                .collect::<Vec<_>>();
+            f.extend((0..E::DEGREE).map(|_| random_polynomial::<F>(options.cp_degree_bound(n))));
+            f
+        } else {
+            f
+        };
+
+        log::info!("Extending to a larger domain");
+        let w = FieldElement::<F>::from(F::GENERATOR);
+        let eval_domain = (0..H_order)
+            .scan(w.clone(), |x, _| {
+                let current = x.clone();
+                *x = &*x * &h;
+                Some(current)
+            })
+            .collect::<Vec<_>>();
+        log::debug!("Coset has {} elements", eval_domain.len());
+
+        log::info!("Evaluating the trace polynomials on the coset");
+        let f_columns = iter!(f)
+            .map(|f_j| evaluate_offset_fft(f_j, H_order, &h, &w))
+            .collect::<Vec<_>>();
+        let f_eval = into_iter!(0..H_order)
+            .map(|i| {
+                f_columns
+                    .iter()
+                    .map(|column| column[i].clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        log::info!("Merkle committing to evaluations, with a row at each leaf");
+        let f_merkle = MerkleTree::<B>::build(&salts.apply(TRACE_TREE, &f_eval));
+        let f_merkle_root = f_merkle.root.clone();
+        log::debug!("Merkle Root: {}", hex::encode(f_merkle_root.to_bytes()));
+        channel.append_bytes(&f_merkle_root.to_bytes());
+
+        /////////////////////////////////////////////////////////////////////////////////
+        ///////////////////////////////////  PART 2  ////////////////////////////////////
+        /////////////////////////////////////////////////////////////////////////////////
+        // the constraints are evaluated point-wise over the coset, where f_j(g^k . x) is
+        // at k * blowup_factor steps ahead of x within the evaluations
+        log::info!("Evaluating the boundary constraints: f_j(g^row) = value");
+        let mut constraints = Vec::new();
+        for bc in &boundary_constraints {
+            let g_row = g.pow(bc.row);
+            let mut denom = iter!(eval_domain)
+                .map(|x| x - &g_row) // x - g^row
+                .collect::<Vec<_>>();
+            batch_inverse(&mut denom).expect("x is not in the trace domain");
+            let evals = iter!(f_columns[bc.column])
+                .zip(into_iter!(denom))
+                .map(|(f_x, denom_inv)| (f_x - &bc.value) * denom_inv) // (f_j - value) / (x - g^row)
+                .collect::<Vec<_>>();
+            constraints.push(evals);
+        }
+
+        log::info!("Evaluating the transition constraints over f_j(x), f_j(g . x), ...");
+        // x^(n+1) takes only blowup_factor distinct values over the coset, as h^(n+1) has that order
+        let mut x_G = (0..blowup_factor)
+            .map(|i| eval_domain[i].pow(G_order) - FieldElement::<F>::one()) // x^(n+1) - 1
+            .collect::<Vec<_>>();
+        FieldElement::inplace_batch_inverse(&mut x_G).expect("x is not in the trace domain");
+        let exemptions = air
+            .transition_exemptions()
+            .into_iter()
+            .map(|row| g.pow(row))
+            .collect::<Vec<_>>();
+        let zerofier_inv = iter!(eval_domain)
+            .enumerate()
+            .map(|(i, x)| {
+                // prod(x - g^row) / (x^(n+1) - 1)
+                exemptions
+                    .iter()
+                    .fold(x_G[i % blowup_factor].clone(), |acc, e| acc * (x - e))
+            })
+            .collect::<Vec<_>>();
+        for tc in &transition_constraints {
+            let evals = into_iter!(0..H_order)
+                .map(|i| {
+                    let frame = (0..window)
+                        .map(|k| f_eval[(i + k * blowup_factor) % H_order].clone())
+                        .collect::<Vec<_>>();
+                    tc.evaluate(&frame, &|c| c.clone()) * &zerofier_inv[i]
+                })
+                .collect::<Vec<_>>();
+            constraints.push(evals);
+        }
+
+        log::info!("Creating the composition polynomial");
+        let alphas = constraints
+            .iter()
+            .map(|_| sample_extension_element::<F, E, T>(&mut channel))
+            .collect::<Vec<_>>();
+        let mut cp_eval = into_iter!(0..H_order)
+            .map(|i| {
+                constraints
+                    .iter()
+                    .zip(&alphas)
+                    .fold(FieldElement::<E>::zero(), |cp_x, (evals, alpha)| {
+                        cp_x + alpha * E::embed(&evals[i])
+                    })
+            })
+            .collect::<Vec<_>>();
+        if options.zero_knowledge {
+            // the masking polynomials are the last columns of the trace, one for each coefficient of cp(x)
+            let width = air.width();
+            iter_mut!(cp_eval)
+                .zip(iter!(f_eval))
+                .for_each(|(cp_x, row)| *cp_x += E::from_base_coeffs(&row[width..]));
+        }
+        // otherwise, FRI would not end up with a constant polynomial
+        let cp = interpolate_offset_fft(&cp_eval, &E::embed(&h), &E::embed(&w));
+        assert!(
+            cp.degree() < options.cp_degree_bound(n),
+            "constraint degree is too high"
+        );
+
+        log::info!(
+            "Merkle committing to the composition polynomial, with an evaluation at each leaf"
+        );
+        let cp_leaves = iter!(cp_eval).map(E::to_base_coeffs).collect::<Vec<_>>();
+        let cp_merkle = MerkleTree::<B>::build(&salts.apply(COMPOSITION_TREE, &cp_leaves));
+        log::debug!("Merkle Root: {}", hex::encode(cp_merkle.root.to_bytes()));
+        channel.append_bytes(&cp_merkle.root.to_bytes());
+
+        log::info!("Evaluating the trace and the composition polynomial at an out-of-domain point");
+        let z = sample_ood_point::<F, E, T>(&mut channel, n, H_order, &w);
+        let ood_frame = (0..window)
+            .map(|k| {
+                let z_k = E::embed(&g.pow(k)) * &z;
+                f.iter()
+                    .map(|f_j| evaluate_at(f_j, &z_k))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let ood_cp = cp.evaluate(&z);
+        for row in &ood_frame {
+            for f_z in row {
+                append_extension_element::<F, E, T>(&mut channel, f_z);
+            }
+        }
+        append_extension_element::<F, E, T>(&mut channel, &ood_cp);
+
+        log::info!("Creating the DEEP composition polynomial");
+        let gammas = (0..window * f.len() + 1)
+            .map(|_| sample_extension_element::<F, E, T>(&mut channel))
+            .collect::<Vec<_>>();
+        let deep_eval = deep_composition_evals(
+            &eval_domain,
+            &f_eval,
+            &cp_eval,
+            &z,
+            &g,
+            &ood_frame,
+            &ood_cp,
+            &gammas,
+        );
+
+        log::info!("Merkle committing to the evaluations, with a coset at each leaf");
+        let deep_merkle =
+            fri_layer_commit::<F, E, B>(&deep_eval, options.fri_folding_factor, &salts, 0);
+        log::debug!("Merkle Root: {}", hex::encode(deep_merkle.root.to_bytes()));
+        channel.append_bytes(&deep_merkle.root.to_bytes());
+
+        /////////////////////////////////////////////////////////////////////////////////
+        ///////////////////////////////////  PART 3  ////////////////////////////////////
+        /////////////////////////////////////////////////////////////////////////////////
+        log::info!("FRI committing to the DEEP composition polynomial");
+        let (_, fri_layers, fri_merkles) = fri_commit_evals::<F, E, B, T>(
+            eval_domain.iter().map(E::embed).collect(),
+            deep_eval,
+            deep_merkle,
+            &salts,
+            options,
+            &mut channel,
+        );
+        assert_eq!(fri_layers.len(), options.num_fri_folds(n) + 1);
+
+        /////////////////////////////////////////////////////////////////////////////////
+        ///////////////////////////////////  PART 4  ////////////////////////////////////
+        /////////////////////////////////////////////////////////////////////////////////
+        log::info!("Grinding with {} bits", options.grinding_bits);
+        let nonce = grind(&channel.state(), options.grinding_bits);
+        log::debug!("Nonce: {}", nonce);
+        channel.append_bytes(&nonce.to_be_bytes());
+
+        log::info!("Generating queries and decommitments to FRI");
+        let (decommitments, trace_proof, cp_proof, fri_proofs) = decommit_fri(
+            options,
+            &mut channel,
+            &f_eval,
+            &f_merkle,
+            &cp_eval,
+            &cp_merkle,
+            &fri_layers,
+            &fri_merkles,
+            &salts,
+        );
+
+        let final_state = hex::encode(channel.state());
+        log::debug!("Final transcript state: {}", final_state);
+
+        /////////////////////////////////////////////////////////////////////////////////
+        ///////////////////////////////////  PROOF   ////////////////////////////////////
+        /////////////////////////////////////////////////////////////////////////////////
+        log::info!("Creating proof object");
+        Stark101Proof {
+            commitment: Stark101Commitment {
+                trace_root: f_merkle_root,
+                composition_root: cp_merkle.root.clone(),
+                cp_roots: fri_merkles.iter().map(|m| m.root.clone()).collect(),
+            },
+            ood_frame,
+            ood_cp,
+            decommitments,
+            trace_proof,
+            cp_proof,
+            fri_proofs,
+            fri_constant: fri_layers.last().unwrap()[0].clone(),
+            nonce,
+            h,
+            w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        babybear::{Babybear31PrimeField, BabybearQuarticField},
+        extension::combine_coeffs,
+        field::{
+            coset_offset, primitive_root_of_unity, Stark101PrimeField,
+            Stark101PrimeFieldBlake3Backend, Stark101PrimeFieldBlake3Transcript,
+            Stark101PrimeFieldElement as FE, Stark101PrimeFieldExtension,
+            Stark101PrimeFieldKeccakBackend, Stark101PrimeFieldPoseidonBackend,
+            Stark101PrimeFieldPoseidonTranscript, Stark101PrimeFieldTranscript,
+        },
+        goldilocks::{Goldilocks64ExtensionField, Goldilocks64Field},
+        program::{
+            counter, fibonacci, fibonacci_registers, fibonacci_square, CounterAir, FibonacciAir,
+            FibonacciRegistersAir, FibonacciSqAir,
+        },
+        verify::{verify, verify_with},
+    };
+    use sha2::{Digest, Sha256};
+
+    fn prove_and_verify_with<B, T>(zero_knowledge: bool)
+    where
+        B: MerkleBackend<Stark101PrimeField>,
+        T: IsTranscript<Stark101PrimeField> + Default,
+    {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions {
+            blowup_factor: 16,
+            zero_knowledge,
+            ..StarkOptions::default()
+        };
+        let proof = Stark101Prover::prove_with::<Stark101PrimeField, Stark101PrimeField, B, T, _>(
+            &air, &trace, &options,
+        );
+
+        let mut channel = T::default();
+        verify_with::<_, _, B, _, _>(&proof, &air, &options, &mut channel);
+    }
+
+    /// Proves and verifies over the field `F` with the challenges from the extension `E`.
+    fn prove_and_verify_extension<F: StarkField, E: ExtensionField<F>>(zero_knowledge: bool) {
+        let (n, a_0, a_1) = (
+            63,
+            FieldElement::<F>::one(),
+            FieldElement::<F>::from(3141592u64),
+        );
+        let trace = vec![fibonacci_square(a_0.clone(), a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1].clone(),
+        };
+        let options = StarkOptions {
+            blowup_factor: 16,
+            zero_knowledge,
+            ..StarkOptions::default()
+        };
+        let proof = Stark101Prover::prove_with::<F, E, Sha256Backend<F>, Keccak256Transcript<F>, _>(
+            &air, &trace, &options,
+        );
+
+        let mut channel = Keccak256Transcript::<F>::default();
+        verify_with::<F, E, Sha256Backend<F>, _, _>(&proof, &air, &options, &mut channel);
+    }
+
+    fn prove_and_verify<A: Air<Stark101PrimeField>>(
+        air: A,
+        trace: Vec<Vec<FE>>,
+        options: StarkOptions,
+    ) {
+        let proof = Stark101Prover::prove(&air, &trace, &options);
+        assert_eq!(proof.decommitments.len(), options.num_queries);
+
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        verify(&proof, &air, &options, &mut channel);
+    }
+
+    #[test]
+    fn test_interpolate_column() {
+        let column = fibonacci_square(FE::from(1u64), FE::from(3141592u64), 15);
+        let g = primitive_root_of_unity(16);
+        let xs = (0..15).map(|i| g.pow(i as u64)).collect::<Vec<_>>();
+        assert_eq!(
+            interpolate_column(&column, &g),
+            Polynomial::interpolate(&xs, &column).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fibonacci_square() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = fibonacci_square(a_0, a_1, n);
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        prove_and_verify(air, vec![trace], StarkOptions::default());
+    }
+
+    #[test]
+    fn test_fibonacci_square_blowup4() {
+        let (n, a_0, a_1) = (63, FE::from(2u64), FE::from(42u64));
+        let trace = fibonacci_square(a_0, a_1, n);
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions {
+            blowup_factor: 4,
+            num_queries: 5,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options);
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let (n, a_0, a_1) = (127, FE::from(1u64), FE::from(rand::random::<u64>()));
+        let trace = fibonacci(a_0, a_1, n);
+        let air = FibonacciAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions {
+            blowup_factor: 16,
+            num_queries: 2,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options);
+    }
+
+    #[test]
+    fn test_fibonacci_folding4() {
+        let (n, a_0, a_1) = (127, FE::from(1u64), FE::from(rand::random::<u64>()));
+        let trace = fibonacci(a_0, a_1, n);
+        let air = FibonacciAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        // 2^7 is not a power of 4, so the last layer has 2 elements
+        let options = StarkOptions {
+            fri_folding_factor: 4,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options);
+    }
+
+    #[test]
+    fn test_fibonacci_square_folding8() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(3141592u64));
+        let trace = fibonacci_square(a_0, a_1, n);
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions {
+            num_queries: 4,
+            fri_folding_factor: 8,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options);
+    }
+
+    #[test]
+    fn test_fibonacci_registers() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(rand::random::<u64>()));
+        let trace = fibonacci_registers(a_0, a_1, n);
+        let air = FibonacciRegistersAir {
+            n,
+            a_0,
+            a_last: trace[1][n - 1],
+        };
+        prove_and_verify(air, trace, StarkOptions::default());
+    }
+
+    #[test]
+    fn test_counter() {
+        let (n, a_0, step) = (63, FE::from(5u64), FE::from(3u64));
+        let trace = counter(a_0, step, n);
+        let air = CounterAir {
+            n,
+            a_0,
+            step,
+            a_last: trace[n - 1],
+        };
+        prove_and_verify(air, vec![trace], StarkOptions::default());
+    }
+
+    #[test]
+    fn test_grinding() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(rand::random::<u64>()));
+        let trace = fibonacci(a_0, a_1, n);
+        let air = FibonacciAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions {
+            grinding_bits: 8,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid proof-of-work nonce")]
+    fn test_wrong_nonce() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci(a_0, a_1, n)];
+        let air = FibonacciAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions {
+            grinding_bits: 12,
+            ..StarkOptions::default()
+        };
+        let mut proof = Stark101Prover::prove(&air, &trace, &options);
+        proof.nonce += 1; // the smallest nonce is found, so the next one is unlikely to be valid as well
+
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        verify(&proof, &air, &options, &mut channel);
+    }
+
+    #[test]
+    #[should_panic(expected = "composition polynomial is inconsistent with the trace")]
+    fn test_wrong_ood_evaluation() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions::default();
+        let mut proof = Stark101Prover::prove(&air, &trace, &options);
+        proof.ood_frame[2][0] += FE::one();
+
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        verify(&proof, &air, &options, &mut channel);
+    }
+
+    #[test]
+    fn test_zero_knowledge() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(3141592u64));
+        let trace = fibonacci_square(a_0, a_1, n);
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions {
+            zero_knowledge: true,
+            ..StarkOptions::default()
+        };
+        prove_and_verify(air, vec![trace], options.clone());
+
+        let trace = fibonacci_registers(a_0, a_1, n);
+        let air = FibonacciRegistersAir {
+            n,
+            a_0,
+            a_last: trace[1][n - 1],
+        };
+        prove_and_verify(air, trace, options);
+    }
+
+    #[test]
+    fn test_zero_knowledge_openings() {
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+
+        // the opened trace values are compared against the unmasked evaluations of the trace polynomial over the
+        // whole coset, so that the query indices do not matter
+        let num_unmasked = |zero_knowledge: bool| {
+            let options = StarkOptions {
+                zero_knowledge,
+                ..StarkOptions::default()
+            };
+            let proof = Stark101Prover::prove(&air, &trace, &options);
+            let H_order = (n + 1) * options.blowup_factor;
+            let g = proof.h.pow(options.blowup_factor);
+            let f = interpolate_column(&trace[0], &g);
+            let lde = evaluate_offset_fft(&f, H_order, &proof.h, &proof.w);
+            proof
+                .decommitments
+                .iter()
+                .filter(|d| lde.contains(&d.trace_row[0]))
+                .count()
+        };
+        assert_eq!(num_unmasked(false), StarkOptions::default().num_queries);
+        assert_eq!(num_unmasked(true), 0);
+
+        // the same statement results in different commitments
+        let options = StarkOptions {
+            zero_knowledge: true,
+            ..StarkOptions::default()
+        };
+        let proof = Stark101Prover::prove(&air, &trace, &options);
+        let other = Stark101Prover::prove(&air, &trace, &options);
+        assert_ne!(proof.commitment.trace_root, other.commitment.trace_root);
+        assert_ne!(proof.commitment.cp_roots, other.commitment.cp_roots);
+    }
+
+    #[test]
+    fn test_zero_knowledge_extension() {
+        type F = Goldilocks64Field;
+        type E = Goldilocks64ExtensionField;
+        let (n, a_0, a_1) = (
+            63,
+            FieldElement::<F>::one(),
+            FieldElement::<F>::from(3141592u64),
+        );
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions {
+            blowup_factor: 16,
+            zero_knowledge: true,
+            ..StarkOptions::default()
+        };
+        let proof = Stark101Prover::prove_with::<F, E, Sha256Backend<F>, Keccak256Transcript<F>, _>(
+            &air, &trace, &options,
+        );
+        let mut channel = Keccak256Transcript::<F>::default();
+        verify_with::<F, E, Sha256Backend<F>, _, _>(&proof, &air, &options, &mut channel);
+
+        // the composition polynomial is masked by m(x) = sum_i e_i . m_i(x), whose coefficients at each query
+        // are the last columns of the trace row, and out-of-domain by m(z)
+        let masked = |v: &FieldElement<E>| {
+            <E as ExtensionField<F>>::to_base_coeffs(v)
+                .iter()
+                .all(|c| *c != FieldElement::zero())
+        };
+        let width = air.width();
+        for d in &proof.decommitments {
+            assert_eq!(d.trace_row.len(), width + <E as ExtensionField<F>>::DEGREE);
+            assert!(masked(&E::from_base_coeffs(&d.trace_row[width..])));
+        }
+        assert!(masked(&combine_coeffs::<F, E>(
+            &proof.ood_frame[0][width..]
+        )));
+
+        // the out-of-domain values of the trace are random in every coordinate as well
+        assert!(proof.ood_frame.iter().flatten().all(masked));
+        assert!(masked(&proof.ood_cp));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions::default();
+        let proof = Stark101Prover::prove(&air, &trace, &options);
+        let other = Stark101Prover::prove(&air, &trace, &options);
+
+        assert_eq!(proof.h, primitive_root_of_unity(256));
+        assert_eq!(proof.w, coset_offset());
+        assert_eq!(
+            serde_json::to_string(&proof).unwrap(),
+            serde_json::to_string(&other).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_proof_bytes() {
+        // the proof is the same with or without the `parallel` feature
+        let (n, a_0, a_1) = (63, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions {
+            num_queries: 8,
+            ..StarkOptions::default()
+        };
+        let proof = Stark101Prover::prove(&air, &trace, &options);
+        let hash = Sha256::digest(proof.to_bytes().unwrap());
+        assert_eq!(
+            hex::encode(hash),
+            "3cbc9ce97c296bc2cf67182c954787eaed3783e49c0864178d6fa6cb671d25f9"
+        );
+    }
+
+    #[test]
+    fn test_keccak() {
+        prove_and_verify_with::<Stark101PrimeFieldKeccakBackend, Stark101PrimeFieldTranscript>(
+            false,
+        );
+    }
+
+    #[test]
+    fn test_blake3() {
+        prove_and_verify_with::<Stark101PrimeFieldBlake3Backend, Stark101PrimeFieldBlake3Transcript>(
+            false,
+        );
+    }
+
+    #[test]
+    fn test_poseidon() {
+        prove_and_verify_with::<
+            Stark101PrimeFieldPoseidonBackend,
+            Stark101PrimeFieldPoseidonTranscript,
+        >(false);
+        prove_and_verify_with::<
+            Stark101PrimeFieldPoseidonBackend,
+            Stark101PrimeFieldPoseidonTranscript,
+        >(true);
+    }
+
+    #[test]
+    fn test_goldilocks() {
+        assert!(field_bits::<Goldilocks64Field, Goldilocks64ExtensionField>() >= 100);
+        prove_and_verify_extension::<Goldilocks64Field, Goldilocks64Field>(false);
+        prove_and_verify_extension::<Goldilocks64Field, Goldilocks64ExtensionField>(false);
+        prove_and_verify_extension::<Goldilocks64Field, Goldilocks64ExtensionField>(true);
+    }
+
+    #[test]
+    fn test_babybear() {
+        assert!(field_bits::<Babybear31PrimeField, BabybearQuarticField>() >= 100);
+        prove_and_verify_extension::<Babybear31PrimeField, BabybearQuarticField>(false);
+        prove_and_verify_extension::<Babybear31PrimeField, BabybearQuarticField>(true);
+    }
+
+    #[test]
+    fn test_stark101_extension() {
+        prove_and_verify_extension::<Stark101PrimeField, Stark101PrimeFieldExtension>(false);
+        prove_and_verify_extension::<Stark101PrimeField, Stark101PrimeFieldExtension>(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Merkle proof for the trace")]
+    fn test_wrong_backend() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = vec![fibonacci_square(a_0, a_1, n)];
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[0][n - 1],
+        };
+        let options = StarkOptions::default();
+        let proof = Stark101Prover::prove_with::<
+            Stark101PrimeField,
+            Stark101PrimeField,
+            Stark101PrimeFieldKeccakBackend,
+            Stark101PrimeFieldTranscript,
+            _,
+        >(&air, &trace, &options);
+
+        // the nodes have the same size, but SHA256 does not open Keccak256 commitments
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        verify(&proof, &air, &options, &mut channel);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_statement() {
+        let (n, a_0, a_1) = (31, FE::from(1u64), FE::from(3141592u64));
+        let trace = fibonacci_square(a_0, a_1, n);
+        let air = FibonacciSqAir {
+            n,
+            a_0,
+            a_last: trace[n - 1],
+        };
+        let options = StarkOptions::default();
+        let proof = Stark101Prover::prove(&air, &[trace], &options);
+
+        // this is the result for n = 1023, not 31
+        let air = FibonacciSqAir {
+            a_last: FE::from(2338775057u64),
+            ..air
+        };
+        let mut channel = Stark101PrimeFieldTranscript::default();
+        verify(&proof, &air, &options, &mut channel);
+    }
+}
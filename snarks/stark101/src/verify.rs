@@ -0,0 +1,405 @@
+#![allow(clippy::too_many_arguments)]
+
+use lambdaworks_crypto::fiat_shamir::is_transcript::IsTranscript;
+use lambdaworks_math::field::{element::FieldElement, traits::IsField};
+
+use crate::{
+    air::Air,
+    deep::{evaluate_deep_composition, sample_ood_point},
+    extension::{
+        append_extension_element, combine_coeffs, flatten, sample_extension_element, ExtensionField,
+    },
+    fft::StarkField,
+    fri::{fold_coset, sample_query_indices},
+    grinding::is_valid_nonce,
+    hash::{MerkleBackend, MerkleNode, Sha256Backend},
+    merkle::{MerkleMultiProof, SALT_LENGTH},
+    options::StarkOptions,
+    proof::{Stark101Decommitment, Stark101Proof},
+};
+
+/// Evaluates the composition polynomial at `x`, using the trace rows at `x`, `g . x`, ... within `frame`,
+/// such that `frame[k][j]` is `f_j(g^k . x)`.
+///
+/// This is the same composition polynomial that the prover builds from `air`, but evaluated at a single point:
+///
+/// - `(f_j(x) - value) / (x - g^row)` for each boundary constraint on column `j`
+/// - `C(f_0(x), f_0(g . x), ...) / Z(x)` for each transition constraint `C`, where
+///   `Z(x) = (x^(n+1) - 1) / prod(x - g^row)` over the exempted rows
+///
+/// summed together with the random coefficients `alphas`. The point `x` is within the extension field, e.g. the
+/// out-of-domain point, and the constants of `air` are embedded into it.
+pub fn evaluate_cp<F: IsField, E: ExtensionField<F>, A: Air<F>>(
+    air: &A,
+    x: &FieldElement<E>,
+    frame: &[Vec<FieldElement<E>>],
+    alphas: &[FieldElement<E>],
+    g: &FieldElement<F>,
+) -> FieldElement<E> {
+    let one = FieldElement::<E>::one();
+    let mut constraints = Vec::new();
+
+    // f_j(g^row) = value
+    for bc in air.boundary_constraints() {
+        let denom = (x - E::embed(&g.pow(bc.row)))
+            .inv()
+            .expect("x is not in the trace domain");
+        constraints.push((&frame[0][bc.column] - E::embed(&bc.value)) * denom);
+    }
+
+    // C(f_0(x), f_0(g . x), ...) = 0
+    let exemptions = air
+        .transition_exemptions()
+        .into_iter()
+        .fold(one.clone(), |acc, row| acc * (x - E::embed(&g.pow(row))));
+    let zerofier = (x.pow(air.trace_length() + 1) - &one)
+        * exemptions.inv().expect("x is not in the trace domain");
+    let zerofier_inv = zerofier.inv().expect("x is not in the trace domain");
+    for tc in air.transition_constraints() {
+        constraints.push(tc.evaluate(frame, &|c| E::embed(c)) * &zerofier_inv);
+    }
+
+    assert_eq!(constraints.len(), alphas.len());
+    constraints
+        .into_iter()
+        .zip(alphas)
+        .fold(FieldElement::<E>::zero(), |acc, (p, alpha)| acc + alpha * p)
+}
+
+/// Verifies the FRI layers of a single query, the counterpart of `decommit_on_fri_layers`.
+///
+/// The evaluation `cp_0(x)` is checked against the DEEP composition polynomial computed from the openings, and then
+/// each layer `cp_(i+1)(y^k)` is checked against the folding of the coset `y . z^t` for `t = 0..k` where `z` is a
+/// `k`-th root of unity, i.e. `cp_i(y)` and `cp_i(-y)` for `k = 2`:
+///
+/// `cp_(i+1)(y^k) = sum_s beta_i^s . y^(-s) / k . sum_t z^(-s . t) . cp_i(y . z^t)`
+///
+/// which for `k = 2` is `(cp_i(y) + cp_i(-y)) / 2 + beta_i * (cp_i(y) - cp_i(-y)) / (2y)`, until we end up at the
+/// constant polynomial.
+///
+/// Returns the index of the coset within each layer, so that the Merkle proofs can be checked for all queries together.
+pub fn verify_on_fri_layers<F: IsField, E: ExtensionField<F>, N>(
+    idx: usize,
+    cp_x: FieldElement<E>,
+    evals: &[FieldElement<E>],
+    proof: &Stark101Proof<F, E, N>,
+    eval_domain_len: usize,
+    folding_factor: usize,
+    betas: &[FieldElement<E>],
+) -> Vec<usize> {
+    let z_inv = E::embed(
+        &proof
+            .h
+            .pow(eval_domain_len / folding_factor)
+            .inv()
+            .expect("h is not zero"),
+    );
+
+    let mut leaf_indices = Vec::new();
+    let mut expected = cp_x;
+    for (i, beta) in betas.iter().enumerate() {
+        let length = eval_domain_len / folding_factor.pow(i as u32);
+        let stride = length / folding_factor;
+        let idx = idx % length;
+        let base_idx = idx % stride;
+
+        // cp_i(y . z^t) for t = 0..k, where y is at base_idx within the layer
+        let coset = &evals[folding_factor * i..folding_factor * (i + 1)];
+        assert_eq!(
+            coset[idx / stride],
+            expected,
+            "FRI layer {} is inconsistent",
+            i
+        );
+        leaf_indices.push(base_idx);
+
+        // fold to find the evaluation on the next layer, at y^k
+        let y = (&proof.w * proof.h.pow(base_idx)).pow(folding_factor.pow(i as u32));
+        let y_inv = E::embed(&y.inv().expect("y is not zero"));
+        expected = fold_coset(coset, &y_inv, &z_inv, beta);
+    }
+    assert_eq!(
+        expected, proof.fri_constant,
+        "last FRI layer is not the committed constant"
+    );
+
+    leaf_indices
+}
+
+/// Verifies a single FRI query, the counterpart of `decommit_on_query`.
+///
+/// We compute the DEEP composition polynomial `D(x)` from the trace row and the composition polynomial at `x`, along
+/// with their out-of-domain evaluations, and call `verify_on_fri_layers` for the rest. The Merkle proofs are
+/// checked afterwards for all queries together, so this returns the indices of the opened FRI cosets.
+///
+/// In zero-knowledge mode, each opened leaf comes with its salt.
+pub fn verify_on_query<F: IsField, E: ExtensionField<F>, N, A: Air<F>>(
+    idx: usize,
+    decommitment: &Stark101Decommitment<F, E>,
+    proof: &Stark101Proof<F, E, N>,
+    air: &A,
+    z: &FieldElement<E>,
+    gammas: &[FieldElement<E>],
+    betas: &[FieldElement<E>],
+    options: &StarkOptions,
+) -> Vec<usize> {
+    let StarkOptions {
+        blowup_factor,
+        fri_folding_factor,
+        ..
+    } = options.clone();
+    let eval_domain_len = (air.trace_length() + 1) * blowup_factor;
+    assert_eq!(
+        decommitment.evals.len(),
+        fri_folding_factor * betas.len(),
+        "wrong number of evaluations"
+    );
+    let (num_salts, salt_length) = if options.zero_knowledge {
+        (2 + betas.len(), SALT_LENGTH)
+    } else {
+        (0, 0)
+    };
+    assert_eq!(decommitment.salts.len(), num_salts, "wrong number of salts");
+    for salt in &decommitment.salts {
+        assert_eq!(salt.len(), salt_length, "wrong salt length");
+    }
+    assert_eq!(
+        decommitment.trace_row.len(),
+        proof.ood_frame[0].len(),
+        "wrong trace width"
+    );
+
+    // the trace domain generator is the evaluation domain generator to the power of blowup
+    let g = proof.h.pow(blowup_factor);
+    let x = &proof.w * proof.h.pow(idx);
+    let deep_x = evaluate_deep_composition(
+        &x,
+        &decommitment.trace_row,
+        &decommitment.cp_eval,
+        z,
+        &g,
+        &proof.ood_frame,
+        &proof.ood_cp,
+        gammas,
+    );
+
+    verify_on_fri_layers(
+        idx,
+        deep_x,
+        &decommitment.evals,
+        proof,
+        eval_domain_len,
+        fri_folding_factor,
+        betas,
+    )
+}
+
+/// Verifies a Stark101 proof that the prover knows a trace satisfying the constraints of `air`.
+///
+/// The verifier replays the transcript in the same order as the prover did, so that it derives the same
+/// `alpha`s, out-of-domain point `z`, `gamma`s, `beta`s and query indices. The evaluation domain is given by
+/// the proof, and the trace domain generator is `g = h^blowup_factor`.
+///
+/// The constraints are checked only at `z`, by computing the composition polynomial from the out-of-domain
+/// frame; the queries then check that the DEEP composition polynomial is of low degree, which ties the trace and
+/// the composition polynomial commitments to the evaluations at `z`.
+///
+/// The challenges are expected to be sampled from the field itself and the Merkle trees to use SHA256, see
+/// `verify_with` for other choices.
+///
+/// ## Panics
+///
+/// If any of the checks fail.
+pub fn verify<F: StarkField, T: IsTranscript<F>, A: Air<F>>(
+    proof: &Stark101Proof<F>,
+    air: &A,
+    options: &StarkOptions,
+    channel: &mut T,
+) {
+    verify_with::<F, F, Sha256Backend<F>, T, A>(proof, air, options, channel)
+}
+
+/// Same as `verify`, but with the extension field `E` and the Merkle tree backend `B` of choice, which must be the
+/// ones that the prover used.
+///
+/// ## Panics
+///
+/// If any of the checks fail.
+pub fn verify_with<F, E, B, T, A>(
+    proof: &Stark101Proof<F, E, B::Node>,
+    air: &A,
+    options: &StarkOptions,
+    channel: &mut T,
+) where
+    F: IsField,
+    E: ExtensionField<F>,
+    B: MerkleBackend<F>,
+    T: IsTranscript<F>,
+    A: Air<F>,
+{
+    let StarkOptions {
+        blowup_factor,
+        num_queries,
+        fri_folding_factor,
+        ..
+    } = options.clone();
+    let n = air.trace_length();
+    options.validate(n);
+    let eval_domain_len = (n + 1) * blowup_factor;
+
+    // h must generate a subgroup of exactly the evaluation domain size, and w must be outside of it
+    let one = FieldElement::<F>::one();
+    assert_eq!(
+        proof.h.pow(eval_domain_len),
+        one,
+        "invalid domain generator"
+    );
+    assert_ne!(
+        proof.h.pow(eval_domain_len >> 1),
+        one,
+        "invalid domain generator"
+    );
+    assert_ne!(proof.w.pow(eval_domain_len), one, "invalid coset offset");
+    assert_eq!(
+        proof.decommitments.len(),
+        num_queries,
+        "wrong number of queries"
+    );
+
+    // the masking polynomials are additional trace columns in zero-knowledge mode, one for each coefficient
+    let width = air.width() + options.zero_knowledge as usize * E::DEGREE;
+    assert_eq!(
+        proof.ood_frame.len(),
+        air.window(),
+        "wrong number of out-of-domain rows"
+    );
+    for row in &proof.ood_frame {
+        assert_eq!(row.len(), width, "wrong trace width");
+    }
+
+    // composition polynomial has degree less than its degree bound, so it takes log_k of that many folds to
+    // reach a constant, and every layer except the last one is committed
+    let cp_roots = &proof.commitment.cp_roots;
+    let num_folds = options.num_fri_folds(n);
+    assert_eq!(cp_roots.len(), num_folds, "wrong number of FRI layers");
+    assert_eq!(
+        proof.fri_proofs.len(),
+        num_folds,
+        "wrong number of FRI proofs"
+    );
+
+    log::info!("Replaying the commitments");
+    channel.append_bytes(&proof.commitment.trace_root.to_bytes());
+    let num_constraints = air.boundary_constraints().len() + air.transition_constraints().len();
+    let alphas = (0..num_constraints)
+        .map(|_| sample_extension_element::<F, E, T>(channel))
+        .collect::<Vec<_>>();
+    channel.append_bytes(&proof.commitment.composition_root.to_bytes());
+
+    log::info!("Verifying the constraints at the out-of-domain point");
+    let z = sample_ood_point::<F, E, T>(channel, n, eval_domain_len, &proof.w);
+    let g = proof.h.pow(blowup_factor);
+    let mut cp_z = evaluate_cp(air, &z, &proof.ood_frame, &alphas, &g);
+    if options.zero_knowledge {
+        cp_z += combine_coeffs::<F, E>(&proof.ood_frame[0][air.width()..]);
+        // masking polynomial m(z)
+    }
+    assert_eq!(
+        cp_z, proof.ood_cp,
+        "composition polynomial is inconsistent with the trace"
+    );
+    for row in &proof.ood_frame {
+        for f_z in row {
+            append_extension_element::<F, E, T>(channel, f_z);
+        }
+    }
+    append_extension_element::<F, E, T>(channel, &proof.ood_cp);
+    let gammas = (0..air.window() * width + 1)
+        .map(|_| sample_extension_element::<F, E, T>(channel))
+        .collect::<Vec<_>>();
+    channel.append_bytes(&cp_roots[0].to_bytes());
+
+    let mut betas = Vec::with_capacity(num_folds);
+    for i in 0..num_folds {
+        betas.push(sample_extension_element::<F, E, T>(channel));
+        if i + 1 < num_folds {
+            channel.append_bytes(&cp_roots[i + 1].to_bytes());
+        }
+    }
+    append_extension_element::<F, E, T>(channel, &proof.fri_constant);
+
+    log::info!("Verifying proof-of-work");
+    assert!(
+        is_valid_nonce(&channel.state(), proof.nonce, options.grinding_bits),
+        "invalid proof-of-work nonce"
+    );
+    channel.append_bytes(&proof.nonce.to_be_bytes());
+
+    log::info!("Verifying {} queries", proof.decommitments.len());
+    let indices = sample_query_indices::<F, T>(channel, num_queries, eval_domain_len as u64);
+    let mut trace_leaves = Vec::new();
+    let mut cp_leaves = Vec::new();
+    let mut fri_leaves = vec![Vec::new(); num_folds];
+    for (idx, decommitment) in indices.into_iter().zip(&proof.decommitments) {
+        log::debug!("Verifying query at index {}", idx);
+        let fri_indices =
+            verify_on_query(idx, decommitment, proof, air, &z, &gammas, &betas, options);
+
+        // leaves are salted in zero-knowledge mode, with the trace row and the composition polynomial first
+        // and then the FRI cosets
+        let salt = |j: usize| decommitment.salts.get(j).cloned().unwrap_or_default();
+        trace_leaves.push((idx, [decommitment.trace_row.clone(), salt(0)].concat()));
+        cp_leaves.push((
+            idx,
+            [E::to_base_coeffs(&decommitment.cp_eval), salt(1)].concat(),
+        ));
+        for (i, leaf_idx) in fri_indices.into_iter().enumerate() {
+            let coset = &decommitment.evals[fri_folding_factor * i..fri_folding_factor * (i + 1)];
+            fri_leaves[i].push((leaf_idx, [flatten::<F, E>(coset), salt(2 + i)].concat()));
+        }
+    }
+
+    log::info!("Verifying the Merkle proofs");
+    assert!(
+        verify_leaves::<F, B>(
+            &proof.trace_proof,
+            &proof.commitment.trace_root,
+            eval_domain_len,
+            &trace_leaves
+        ),
+        "invalid Merkle proof for the trace"
+    );
+    assert!(
+        verify_leaves::<F, B>(
+            &proof.cp_proof,
+            &proof.commitment.composition_root,
+            eval_domain_len,
+            &cp_leaves
+        ),
+        "invalid Merkle proof for the composition polynomial"
+    );
+    for (i, leaves) in fri_leaves.iter().enumerate() {
+        let num_leaves = eval_domain_len / fri_folding_factor.pow(i as u32 + 1);
+        assert!(
+            verify_leaves::<F, B>(&proof.fri_proofs[i], &cp_roots[i], num_leaves, leaves),
+            "invalid Merkle proof for FRI layer {}",
+            i
+        );
+    }
+
+    log::info!("Proof verified");
+}
+
+/// Verifies a Merkle multi-proof for the given (already salted) leaves.
+fn verify_leaves<F: IsField, B: MerkleBackend<F>>(
+    proof: &MerkleMultiProof<B::Node>,
+    root: &B::Node,
+    num_leaves: usize,
+    leaves: &[(usize, Vec<FieldElement<F>>)],
+) -> bool {
+    let leaves = leaves
+        .iter()
+        .map(|(leaf_idx, leaf)| (*leaf_idx, leaf))
+        .collect::<Vec<_>>();
+    proof.verify::<B>(root, num_leaves, &leaves)
+}